@@ -0,0 +1,95 @@
+//! Add a task to a devlog entry file without opening an editor.
+
+use crate::config::Config;
+use crate::error::Error;
+use crate::file::{append_tasks, LogFile, ParseMode};
+use crate::git;
+use crate::hook::{execute_hook, HookContext, HookType};
+use crate::json;
+use crate::lock::{self, RepoLock};
+use crate::path::LogPath;
+use crate::plugin;
+use crate::task::Task;
+use std::ffi::OsStr;
+use std::io::Write;
+
+/// Appends the task to the end of the devlog entry file.
+/// If available, the after-add hook is invoked, and registered plugins are notified.
+/// Afterwards, changes are committed if the repository is a git repository with auto-commit enabled.
+/// Fails with `Error::RepoLocked` if another devlog process is writing to the repository.
+pub fn add<W: Write>(w: &mut W, config: &Config, p: &LogPath, task: &Task) -> Result<(), Error> {
+    let lock = lock::lock(config)?;
+    add_with_lock(w, config, &lock, p, task)
+}
+
+/// Same as `add`, but for a caller that already holds the repository lock.
+pub fn add_with_lock<W: Write>(
+    w: &mut W,
+    config: &Config,
+    _lock: &RepoLock,
+    p: &LogPath,
+    task: &Task,
+) -> Result<(), Error> {
+    let path = p.path();
+    append_tasks(path, std::slice::from_ref(task)).map_err(Error::at_path("append to", path))?;
+
+    // The new task is the last task in the file.
+    let ctx = HookContext::new("add", Some(p.seq_num()));
+    let ctx = if config.hook_json() {
+        let f = LogFile::load_with_mode(path, ParseMode::Lossy)?;
+        let added = f
+            .tasks_with_line_nums()
+            .last()
+            .map(|(n, t)| json::task_value(p, n, t));
+        ctx.with_tasks(added.into_iter().collect())
+    } else {
+        ctx
+    };
+    let args = [path.as_os_str(), OsStr::new(task.content())];
+    execute_hook(w, config, &HookType::AfterAdd, &args, &ctx)?;
+    plugin::notify(w, config, &HookType::AfterAdd, |plugin| {
+        plugin.after_add(p, task)
+    })?;
+
+    git::autocommit(w, config, &format!("Add task to entry {}", p.seq_num()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::LogRepository;
+    use crate::task::TaskStatus;
+    use std::fs::{create_dir_all, read_to_string, set_permissions, File, Permissions};
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_add() {
+        let mut out = Vec::new();
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let config = Config::new(dir.path(), "");
+        let logpath = repo.init().unwrap();
+
+        let hook_dir = dir.path().join("hooks");
+        create_dir_all(&hook_dir).unwrap();
+        let hook_path = hook_dir.join(HookType::AfterAdd.name());
+        let mut f = File::create(&hook_path).unwrap();
+        writeln!(
+            f,
+            "#!/usr/bin/env sh\necho \"$2\" > \"$DEVLOG_REPO/added.out\""
+        )
+        .unwrap();
+        drop(f);
+        set_permissions(&hook_path, Permissions::from_mode(0o555)).unwrap();
+
+        let task = Task::new(TaskStatus::Started, "Write release notes");
+        add(&mut out, &config, &logpath, &task).unwrap();
+
+        let logfile = LogFile::load(logpath.path()).unwrap();
+        assert_eq!(logfile.tasks().last(), Some(&task));
+        let added = read_to_string(dir.path().join("added.out")).unwrap();
+        assert_eq!(added, "Write release notes\n");
+    }
+}
@@ -1,10 +1,18 @@
 extern crate clap;
 extern crate devlog;
 
-use clap::{Arg, ArgMatches, Command};
-use devlog::{editor, hook, rollover, status, Config, Error, LogRepository, TaskStatus};
-use std::fs::File;
-use std::io::{copy, stdin, stdout, Write};
+use clap::{Arg, ArgGroup, ArgMatches, Command};
+use devlog::hook::{HookContext, HookType};
+use devlog::lock::RepoLock;
+use devlog::{
+    add, color, csv, editor, git, hook, html, ical, json, lint, lock, markdown, org, plugin,
+    report, rollover, standup, status, taskwarrior, template, todotxt, Config, Error, LogFile,
+    LogRepository, ParseMode, Task, TaskStatus,
+};
+use std::ffi::{OsStr, OsString};
+use std::fs::{read_to_string, File};
+use std::io::{copy, stdin, stdout, BufRead, BufReader, IsTerminal, Write};
+use std::path::Path;
 use std::process::exit;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -15,12 +23,62 @@ const MAIN_INFO: &str =
 const EDIT_INFO: &str =
     "Uses the editor program $DEVLOG_EDITOR, which defaults to nano if not set.";
 
-fn main() -> Result<(), Error> {
+const SYNC_INFO: &str =
+    "The repository must have a remote named \"origin\". If the current branch has no upstream branch, it is pushed to origin.";
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("Error: {}", err);
+        exit(err.exit_code());
+    }
+}
+
+fn run() -> Result<(), Error> {
     let yes_arg = Arg::new("yes")
         .short('y')
         .long("yes")
         .help("Automatically answer \"yes\" in response to all prompts.");
 
+    let format_arg = Arg::new("format")
+        .long("format")
+        .takes_value(true)
+        .value_name("FORMAT")
+        .possible_values(["text", "json"])
+        .default_value("text")
+        .help("Output format");
+
+    let color_arg = Arg::new("color")
+        .long("color")
+        .takes_value(true)
+        .value_name("WHEN")
+        .possible_values(["auto", "always", "never"])
+        .default_value("auto")
+        .help("Highlight tasks by status");
+
+    let template_arg = Arg::new("template")
+        .long("template")
+        .takes_value(true)
+        .value_name("TEMPLATE")
+        .conflicts_with("format")
+        .help("Print each task using a template, e.g. \"{seq}:{line} [{status}] {content}\"");
+
+    let back_arg = Arg::new("back")
+        .short('b')
+        .long("back")
+        .takes_value(true)
+        .value_name("BACK")
+        .default_value("0")
+        .help("Show tasks from a previous devlog");
+
+    let hook_arg = Arg::new("hook")
+        .value_name("HOOK")
+        .required(true)
+        .help("Name of the hook, e.g. after-edit");
+
+    let input_arg = Arg::new("input")
+        .value_name("FILE")
+        .help("File to import (defaults to stdin)");
+
     let m = Command::new("devlog")
         .about("Track daily development work")
         .after_help(MAIN_INFO)
@@ -29,18 +87,54 @@ fn main() -> Result<(), Error> {
         .subcommand(
             Command::new("init")
                 .about("Initialize a new devlog repository if it does not already exist.")
-                .arg(yes_arg.clone()),
+                .arg(yes_arg.clone())
+                .arg(
+                    Arg::new("git")
+                        .long("git")
+                        .help("Also initialize a git repository and commit changes after edit and rollover"),
+                ),
         )
         .subcommand(
             Command::new("edit")
                 .about("Edit the most recent devlog file")
                 .after_help(EDIT_INFO)
-                .arg(yes_arg.clone()),
+                .arg(yes_arg.clone())
+                .arg(
+                    Arg::new("lint")
+                        .long("lint")
+                        .help("Check the devlog file after editing and offer to re-open the editor if there are problems"),
+                ),
+        )
+        .subcommand(
+            Command::new("add")
+                .about("Add a task to the most recent devlog file")
+                .arg(
+                    Arg::new("status")
+                        .short('s')
+                        .long("status")
+                        .takes_value(true)
+                        .value_name("STATUS")
+                        .possible_values(["todo", "started", "blocked", "done"])
+                        .default_value("todo")
+                        .help("Status of the new task"),
+                )
+                .arg(
+                    Arg::new("content")
+                        .value_name("TASK")
+                        .required(true)
+                        .multiple_values(true)
+                        .help("Description of the task"),
+                ),
         )
         .subcommand(
             Command::new("rollover")
                 .about("Create new devlog file with incomplete and blocked tasks from the current devlog file")
-                .arg(yes_arg.clone()),
+                .arg(yes_arg.clone())
+                .arg(
+                    Arg::new("lossy")
+                        .long("lossy")
+                        .help("Replace invalid UTF-8 in the current devlog file instead of failing"),
+                ),
         )
         .subcommand(
             Command::new("status")
@@ -51,20 +145,168 @@ fn main() -> Result<(), Error> {
                         .long("show")
                         .takes_value(true)
                         .value_name("SHOW")
-                        .possible_values(&["all", "todo", "started", "blocked", "done"])
+                        .possible_values(["all", "todo", "started", "blocked", "done"])
                         .default_value("all")
                         .help("Sections to show"),
                 )
+                .arg(back_arg.clone())
+                .arg(format_arg.clone())
+                .arg(color_arg.clone())
+                .arg(template_arg.clone()),
+        )
+        .subcommand(
+            Command::new("export")
+                .about("Export devlog entries to other formats")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("csv")
+                        .about("Print every task in every devlog file as CSV")
+                        .arg(
+                            Arg::new("collapse")
+                                .long("collapse")
+                                .help("Write one row per task, with the first and last entries it appears in"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("html")
+                        .about("Export all devlog entries as a static HTML site")
+                        .arg(
+                            Arg::new("dir")
+                                .required(true)
+                                .value_name("DIR")
+                                .help("Directory in which to write the site"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("ical").about(
+                        "Print tasks with due or scheduled dates from all devlog entries as iCalendar",
+                    ),
+                )
+                .subcommand(
+                    Command::new("markdown")
+                        .about("Print tasks from a devlog file as a Markdown checklist")
+                        .arg(back_arg.clone()),
+                )
+                .subcommand(
+                    Command::new("org").about("Print all devlog entries in Org mode format"),
+                )
+                .subcommand(
+                    Command::new("todotxt")
+                        .about("Print tasks from the most recent devlog file in todo.txt format"),
+                )
+                .subcommand(
+                    Command::new("taskwarrior").about(
+                        "Print tasks from the most recent devlog file as Taskwarrior JSON",
+                    ),
+                ),
+        )
+        .subcommand(
+            Command::new("import")
+                .about("Append tasks from other formats to the most recent devlog file")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("markdown")
+                        .about("Import items from a Markdown checklist")
+                        .arg(input_arg.clone()),
+                )
+                .subcommand(
+                    Command::new("org")
+                        .about("Import Org mode headings with TODO keywords")
+                        .arg(input_arg.clone()),
+                )
+                .subcommand(
+                    Command::new("todotxt")
+                        .about("Import tasks in todo.txt format")
+                        .arg(input_arg.clone()),
+                )
+                .subcommand(
+                    Command::new("taskwarrior")
+                        .about("Import tasks from Taskwarrior JSON (the output of `task export`)")
+                        .arg(input_arg.clone()),
+                ),
+        )
+        .subcommand(
+            Command::new("hooks")
+                .about("List, enable, disable, and test the hooks of the devlog repository")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("list")
+                        .about("Show each hook and whether it is enabled"),
+                )
+                .subcommand(
+                    Command::new("enable")
+                        .about("Make a hook file executable")
+                        .arg(hook_arg.clone()),
+                )
+                .subcommand(
+                    Command::new("disable")
+                        .about("Make a hook file non-executable")
+                        .arg(hook_arg.clone()),
+                )
+                .subcommand(
+                    Command::new("run")
+                        .about("Run a hook with sample arguments based on the most recent devlog file")
+                        .arg(hook_arg.clone())
+                        .arg(
+                            Arg::new("args")
+                                .value_name("ARGS")
+                                .multiple_values(true)
+                                .allow_invalid_utf8(true)
+                                .help("Arguments to pass to the hook instead of the sample arguments"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("reinit")
+                        .about("Re-create missing hook files from the template"),
+                ),
+        )
+        .subcommand(
+            Command::new("lint")
+                .about("Check a devlog file for malformed tasks and unclosed code blocks")
+                .arg(back_arg.clone()),
+        )
+        .subcommand(
+            Command::new("report")
+                .about("Show a Markdown summary of the tasks from the current week or month")
+                .arg(
+                    Arg::new("week")
+                        .long("week")
+                        .help("Summarize entries from the current week (default)"),
+                )
+                .arg(
+                    Arg::new("month")
+                        .long("month")
+                        .help("Summarize entries from the current month"),
+                )
+                .group(ArgGroup::new("period").args(&["week", "month"])),
+        )
+        .subcommand(
+            Command::new("standup")
+                .about("Show a standup report of tasks completed yesterday, planned for today, and blocked")
                 .arg(
-                    Arg::new("back")
-                        .short('b')
-                        .long("back")
+                    Arg::new("format")
+                        .short('f')
+                        .long("format")
                         .takes_value(true)
-                        .value_name("BACK")
-                        .default_value("0")
-                        .help("Show tasks from a previous devlog"),
+                        .value_name("FORMAT")
+                        .possible_values(["plain", "markdown", "slack"])
+                        .default_value("plain")
+                        .help("Output format"),
+                )
+                .arg(
+                    Arg::new("template")
+                        .short('t')
+                        .long("template-file")
+                        .takes_value(true)
+                        .value_name("TEMPLATE_FILE")
+                        .help("File containing a report template with {yesterday}, {today}, and {blocked} placeholders"),
                 ),
         )
+        .subcommand(
+            Command::new("sync")
+                .about("Commit changes, then pull and push the devlog git repository")
+                .after_help(SYNC_INFO),
+        )
         .subcommand(
             Command::new("tail")
                 .about("Show recent devlogs")
@@ -76,7 +318,10 @@ fn main() -> Result<(), Error> {
                         .value_name("LIMIT")
                         .help("Maximum number of log files to display")
                         .default_value("2"),
-                ),
+                )
+                .arg(format_arg.clone())
+                .arg(color_arg.clone())
+                .arg(template_arg.clone()),
         )
         .get_matches();
 
@@ -84,8 +329,16 @@ fn main() -> Result<(), Error> {
     match m.subcommand() {
         Some(("init", m)) => init_cmd(&mut w, m),
         Some(("edit", m)) => edit_cmd(&mut w, m),
+        Some(("add", m)) => add_cmd(&mut w, m),
         Some(("rollover", m)) => rollover_cmd(&mut w, m),
         Some(("status", m)) => status_cmd(&mut w, m),
+        Some(("export", m)) => export_cmd(&mut w, m),
+        Some(("import", m)) => import_cmd(&mut w, m),
+        Some(("hooks", m)) => hooks_cmd(&mut w, m),
+        Some(("lint", m)) => lint_cmd(&mut w, m),
+        Some(("report", m)) => report_cmd(&mut w, m),
+        Some(("standup", m)) => standup_cmd(&mut w, m),
+        Some(("sync", m)) => sync_cmd(&mut w, m),
         Some(("tail", m)) => tail_cmd(&mut w, m),
         _ => panic!("No subcommand"),
     }
@@ -109,16 +362,16 @@ fn prompt_confirm<W: Write>(w: &mut W, msg: &str, m: &ArgMatches) -> Result<bool
         .map_err(From::from)
 }
 
-fn abort_if_not_initialized<W: Write>(w: &mut W, repo: &LogRepository) -> Result<(), Error> {
+fn abort_if_not_initialized(repo: &LogRepository) -> Result<(), Error> {
     if !repo.initialized()? {
-        write!(w, "Repository at {:?} has not been initialized.\nPlease run `devlog init` to initialize the repository.\n", repo.path())?;
-        exit(1);
+        return Err(Error::NotInitialized(repo.path().to_path_buf()));
     }
     Ok(())
 }
 
 fn initialize_if_necessary<W: Write>(
     w: &mut W,
+    config: &Config,
     repo: &LogRepository,
     m: &ArgMatches,
 ) -> Result<bool, Error> {
@@ -127,8 +380,14 @@ fn initialize_if_necessary<W: Write>(
     } else {
         let msg = format!("Initialize devlog repository at {:?}?", repo.path());
         if prompt_confirm(w, &msg, m)? {
-            repo.init()?;
+            let logpath = repo.init()?;
             hook::init_hooks(repo.path())?;
+            let ctx = HookContext::new("init", Some(logpath.seq_num()));
+            let args = [logpath.path().as_os_str()];
+            hook::execute_hook(w, config, &HookType::AfterInit, &args, &ctx)?;
+            plugin::notify(w, config, &HookType::AfterInit, |plugin| {
+                plugin.after_init(&logpath)
+            })?;
         } else {
             exit(0);
         }
@@ -139,7 +398,11 @@ fn initialize_if_necessary<W: Write>(
 fn init_cmd<W: Write>(w: &mut W, m: &ArgMatches) -> Result<(), Error> {
     let config = Config::load();
     let repo = LogRepository::new(config.repo_dir());
-    initialize_if_necessary(w, &repo, m).and_then(|created| {
+    initialize_if_necessary(w, &config, &repo, m).and_then(|created| {
+        if m.is_present("git") {
+            git::init(repo.path())?;
+            writeln!(w, "Initialized git repository in {:?}", repo.path())?;
+        }
         if created {
             writeln!(
                 w,
@@ -155,13 +418,68 @@ fn init_cmd<W: Write>(w: &mut W, m: &ArgMatches) -> Result<(), Error> {
 fn edit_cmd<W: Write>(w: &mut W, m: &ArgMatches) -> Result<(), Error> {
     let config = Config::load();
     let repo = LogRepository::new(config.repo_dir());
-    initialize_if_necessary(w, &repo, m).and_then(|_| match repo.latest()? {
-        Some(logpath) => editor::open(w, &config, logpath.path()),
-        None => {
+    initialize_if_necessary(w, &config, &repo, m).and_then(|_| {
+        // Find the latest entry under the lock, in case another command rolls over first.
+        let lock = lock::lock(&config)?;
+        let logpath = match lock.latest()? {
+            Some(logpath) => logpath,
             // The user already confirmed initialization of the repo,
             // so if we don't find it we initialize it again to ensure it exists.
-            repo.init()
-                .and_then(|logpath| editor::open(w, &config, logpath.path()))
+            None => repo.init()?,
+        };
+        editor::open_with_lock(w, &config, &lock, logpath.path())?;
+        if m.is_present("lint") {
+            lint_after_edit(w, &config, &lock, logpath.path(), m)?;
+        }
+        Ok(())
+    })
+}
+
+fn lint_after_edit<W: Write>(
+    w: &mut W,
+    config: &Config,
+    lock: &RepoLock,
+    path: &Path,
+    m: &ArgMatches,
+) -> Result<(), Error> {
+    // With --yes there is nobody to fix the problems, so report them without re-opening.
+    while lint::print(w, path)? > 0 && !m.is_present("yes") {
+        if !prompt_confirm(w, "Re-open the editor to fix these problems?", m)? {
+            break;
+        }
+        editor::open_with_lock(w, config, lock, path)?;
+    }
+    Ok(())
+}
+
+fn add_cmd<W: Write>(w: &mut W, m: &ArgMatches) -> Result<(), Error> {
+    let status = match m.value_of("status") {
+        Some("todo") => TaskStatus::ToDo,
+        Some("started") => TaskStatus::Started,
+        Some("blocked") => TaskStatus::Blocked,
+        Some("done") => TaskStatus::Done,
+        _ => panic!("Invalid value for status arg"),
+    };
+    let content = m
+        .values_of("content")
+        .map(|v| v.collect::<Vec<&str>>().join(" "))
+        .unwrap_or_default();
+    if content.trim().is_empty() {
+        return Err(Error::InvalidArg("task must not be empty"));
+    }
+
+    let config = Config::load();
+    let repo = LogRepository::new(config.repo_dir());
+    abort_if_not_initialized(&repo).and_then(|()| {
+        let lock = lock::lock(&config)?;
+        match lock.latest()? {
+            Some(p) => {
+                add::add_with_lock(w, &config, &lock, &p, &Task::new(status, content.trim()))?;
+                writeln!(w, "Added task to {:?}", p.path()).map_err(From::from)
+            }
+            // This will only occur if something deleted the repo
+            // right after we checked that it was initialized (unlikely)
+            None => Err(Error::NotInitialized(repo.path().to_path_buf())),
         }
     })
 }
@@ -169,31 +487,31 @@ fn edit_cmd<W: Write>(w: &mut W, m: &ArgMatches) -> Result<(), Error> {
 fn rollover_cmd<W: Write>(w: &mut W, m: &ArgMatches) -> Result<(), Error> {
     let config = Config::load();
     let repo = LogRepository::new(config.repo_dir());
-    abort_if_not_initialized(w, &repo).and_then(|()| {
-        match repo.latest()? {
+    abort_if_not_initialized(&repo).and_then(|()| {
+        if !prompt_confirm(w, "Rollover incomplete tasks?", m)? {
+            return Ok(());
+        }
+        let mode = if m.is_present("lossy") {
+            ParseMode::Lossy
+        } else {
+            ParseMode::Strict
+        };
+        let lock = lock::lock(&config)?;
+        match lock.latest()? {
             Some(p) => {
-                if prompt_confirm(w, "Rollover incomplete tasks?", m)? {
-                    let (logpath, count) = rollover::rollover(w, &config, &p)?;
-                    writeln!(w, "Imported {} tasks into {:?}", count, logpath.path())?;
-                }
+                let (logpath, count) = rollover::rollover_with_lock(w, &config, &lock, &p, mode)?;
+                writeln!(w, "Imported {} tasks into {:?}", count, logpath.path())?;
                 Ok(())
             }
-            None => {
-                // This will only occur if something deleted the repo
-                // right after we checked that it was initialized (unlikely)
-                writeln!(w, "Could not find devlog file to rollover")?;
-                exit(1)
-            }
+            // This will only occur if something deleted the repo
+            // right after we checked that it was initialized (unlikely)
+            None => Err(Error::NotInitialized(repo.path().to_path_buf())),
         }
     })
 }
 
 fn status_cmd<W: Write>(w: &mut W, m: &ArgMatches) -> Result<(), Error> {
-    let num_back = m
-        .value_of("back")
-        .unwrap()
-        .parse::<usize>()
-        .map_err(|_| Error::InvalidArg("back must be an integer"))?;
+    let num_back = parse_back_arg(m)?;
 
     let display_mode = match m.value_of("show") {
         Some("all") => status::DisplayMode::ShowAll,
@@ -206,7 +524,253 @@ fn status_cmd<W: Write>(w: &mut W, m: &ArgMatches) -> Result<(), Error> {
 
     let config = Config::load();
     let repo = LogRepository::new(config.repo_dir());
-    abort_if_not_initialized(w, &repo).and_then(|_| status::print(w, &repo, num_back, display_mode))
+    abort_if_not_initialized(&repo).and_then(|_| {
+        let logpath = repo.nth_from_latest(num_back)?;
+        let seq_num = logpath.as_ref().map(|p| p.seq_num());
+        let args: Vec<&OsStr> = logpath.iter().map(|p| p.path().as_os_str()).collect();
+        let ctx = HookContext::new("status", seq_num);
+
+        hook::execute_hook(w, &config, &HookType::BeforeStatus, &args, &ctx)?;
+        plugin::notify(w, &config, &HookType::BeforeStatus, |plugin| {
+            plugin.before_status(logpath.as_ref())
+        })?;
+        if is_json_format(m) {
+            json::print_status(w, &repo, num_back, display_mode)?;
+        } else if let Some(tmpl) = parse_template_arg(m)? {
+            template::print_status(w, &repo, num_back, display_mode, &tmpl)?;
+        } else {
+            status::print_highlighted(w, &repo, num_back, display_mode, painter(m))?;
+        }
+        hook::execute_hook(w, &config, &HookType::AfterStatus, &args, &ctx)?;
+        plugin::notify(w, &config, &HookType::AfterStatus, |plugin| {
+            plugin.after_status(logpath.as_ref())
+        })
+    })
+}
+
+fn export_cmd<W: Write>(w: &mut W, m: &ArgMatches) -> Result<(), Error> {
+    let config = Config::load();
+    let repo = LogRepository::new(config.repo_dir());
+    abort_if_not_initialized(&repo).and_then(|_| match m.subcommand() {
+        Some(("csv", m)) => csv::export(w, &repo, m.is_present("collapse")).map(|_| ()),
+        Some(("html", m)) => {
+            let dir = Path::new(m.value_of("dir").unwrap());
+            let count = html::export(&repo, dir)?;
+            writeln!(w, "Exported {} entries to {:?}", count, dir).map_err(From::from)
+        }
+        Some(("ical", _)) => ical::export(w, &repo).map(|_| ()),
+        Some(("markdown", m)) => markdown::export(w, &repo, parse_back_arg(m)?),
+        Some(("org", _)) => org::export(w, &repo),
+        Some(("todotxt", _)) => todotxt::export(w, &repo),
+        Some(("taskwarrior", _)) => taskwarrior::export(w, &repo),
+        _ => panic!("No export subcommand"),
+    })
+}
+
+fn import_cmd<W: Write>(w: &mut W, m: &ArgMatches) -> Result<(), Error> {
+    let config = Config::load();
+    let repo = LogRepository::new(config.repo_dir());
+    abort_if_not_initialized(&repo)?;
+    let _lock = lock::lock(&config)?;
+    let count = match m.subcommand() {
+        Some(("markdown", m)) => markdown::import(open_input(m)?, &repo)?,
+        Some(("org", m)) => org::import(open_input(m)?, &repo)?,
+        Some(("todotxt", m)) => todotxt::import(open_input(m)?, &repo)?,
+        Some(("taskwarrior", m)) => taskwarrior::import(open_input(m)?, &repo)?,
+        _ => panic!("No import subcommand"),
+    };
+    writeln!(w, "Imported {} tasks", count).map_err(From::from)
+}
+
+fn open_input(m: &ArgMatches) -> Result<Box<dyn BufRead>, Error> {
+    match m.value_of("input") {
+        Some(p) => Ok(Box::new(BufReader::new(File::open(p)?))),
+        None => Ok(Box::new(BufReader::new(stdin()))),
+    }
+}
+
+fn hooks_cmd<W: Write>(w: &mut W, m: &ArgMatches) -> Result<(), Error> {
+    let config = Config::load();
+    let repo = LogRepository::new(config.repo_dir());
+    abort_if_not_initialized(&repo).and_then(|()| match m.subcommand() {
+        Some(("list", _)) => list_hooks(w, repo.path()),
+        Some(("enable", m)) => {
+            let hook_type = parse_hook_arg(m)?;
+            hook::set_hook_enabled(repo.path(), &hook_type, true)?;
+            writeln!(w, "Enabled the {} hook", hook_type.name()).map_err(From::from)
+        }
+        Some(("disable", m)) => {
+            let hook_type = parse_hook_arg(m)?;
+            hook::set_hook_enabled(repo.path(), &hook_type, false)?;
+            writeln!(w, "Disabled the {} hook", hook_type.name()).map_err(From::from)
+        }
+        Some(("run", m)) => run_hook(w, &config, &repo, m),
+        Some(("reinit", _)) => {
+            let created = hook::init_hooks(repo.path())?;
+            for hook_type in created.iter() {
+                writeln!(w, "Created the {} hook (disabled)", hook_type.name())?;
+            }
+            if created.is_empty() {
+                writeln!(w, "All hook files already exist")?;
+            }
+            Ok(())
+        }
+        _ => panic!("No hooks subcommand"),
+    })
+}
+
+fn list_hooks<W: Write>(w: &mut W, repo_dir: &Path) -> Result<(), Error> {
+    for hook_type in HookType::all() {
+        let state = match hook::hook_file_state(repo_dir, hook_type)? {
+            hook::HookFileState::Enabled => "enabled",
+            hook::HookFileState::Disabled => "disabled",
+            hook::HookFileState::Missing => "missing",
+        };
+        let mut line = format!("{:<16} {:<8}", hook_type.name(), state);
+        let scripts = hook::hook_d_scripts(repo_dir, hook_type)?;
+        if !scripts.is_empty() {
+            line.push_str(&format!("  +{} in {}.d", scripts.len(), hook_type.name()));
+        }
+        writeln!(w, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+// Runs the hook the same way the devlog command would, with DEVLOG_COMMAND set to "hooks".
+// Unless the user passes arguments, the sample arguments refer to the most recent devlog file.
+fn run_hook<W: Write>(
+    w: &mut W,
+    config: &Config,
+    repo: &LogRepository,
+    m: &ArgMatches,
+) -> Result<(), Error> {
+    let hook_type = parse_hook_arg(m)?;
+    let num_scripts = hook::hook_cmds(repo.path(), &hook_type)?.len();
+    if num_scripts == 0 {
+        return writeln!(
+            w,
+            "The {0} hook is not enabled; run `devlog hooks enable {0}` to enable it",
+            hook_type.name()
+        )
+        .map_err(From::from);
+    }
+
+    let latest = match repo.latest()? {
+        Some(p) => p,
+        None => return Err(Error::NotInitialized(repo.path().to_path_buf())),
+    };
+    let previous = match repo.nth_from_latest(1)? {
+        Some(p) => p.path().as_os_str().to_owned(),
+        None => latest.path().as_os_str().to_owned(),
+    };
+    let args: Vec<OsString> = match m.values_of_os("args") {
+        Some(args) => args.map(OsString::from).collect(),
+        None => match hook_type {
+            HookType::AfterRollover => vec![previous, latest.path().as_os_str().to_owned()],
+            HookType::AfterAdd | HookType::TaskCompleted => vec![
+                latest.path().as_os_str().to_owned(),
+                OsString::from("Sample task"),
+            ],
+            _ => vec![latest.path().as_os_str().to_owned()],
+        },
+    };
+    let args: Vec<&OsStr> = args.iter().map(OsString::as_os_str).collect();
+
+    let tasks = LogFile::load_with_mode(latest.path(), ParseMode::Lossy)?
+        .tasks_with_line_nums()
+        .map(|(n, t)| json::task_value(&latest, n, t))
+        .collect();
+    let ctx = HookContext::new("hooks", Some(latest.seq_num())).with_tasks(tasks);
+    writeln!(
+        w,
+        "Running {} script(s) for the {} hook",
+        num_scripts,
+        hook_type.name()
+    )?;
+    hook::execute_hook(w, config, &hook_type, &args, &ctx)
+}
+
+fn parse_hook_arg(m: &ArgMatches) -> Result<HookType, Error> {
+    m.value_of("hook")
+        .and_then(HookType::from_name)
+        .ok_or(Error::InvalidArg(
+            "unknown hook; run `devlog hooks list` to see the available hooks",
+        ))
+}
+
+fn lint_cmd<W: Write>(w: &mut W, m: &ArgMatches) -> Result<(), Error> {
+    let num_back = parse_back_arg(m)?;
+    let config = Config::load();
+    let repo = LogRepository::new(config.repo_dir());
+    abort_if_not_initialized(&repo).and_then(|()| {
+        if let Some(logpath) = repo.nth_from_latest(num_back)? {
+            let count = lint::print(w, logpath.path())?;
+            if count > 0 {
+                return Err(Error::LintFailed {
+                    path: logpath.path().to_path_buf(),
+                    count,
+                });
+            }
+            writeln!(w, "No problems found in {:?}", logpath.path())?;
+        }
+        Ok(())
+    })
+}
+
+fn report_cmd<W: Write>(w: &mut W, m: &ArgMatches) -> Result<(), Error> {
+    let period = if m.is_present("month") {
+        report::Period::Month
+    } else {
+        report::Period::Week
+    };
+
+    let config = Config::load();
+    let repo = LogRepository::new(config.repo_dir());
+    abort_if_not_initialized(&repo).and_then(|_| report::print(w, &repo, period))
+}
+
+fn standup_cmd<W: Write>(w: &mut W, m: &ArgMatches) -> Result<(), Error> {
+    let fmt = match m.value_of("format") {
+        Some("plain") => standup::OutputFormat::Plain,
+        Some("markdown") => standup::OutputFormat::Markdown,
+        Some("slack") => standup::OutputFormat::Slack,
+        _ => panic!("Invalid value for format arg"),
+    };
+
+    let template = match m.value_of("template") {
+        Some(p) => Some(read_to_string(p)?),
+        None => None,
+    };
+
+    let config = Config::load();
+    let repo = LogRepository::new(config.repo_dir());
+    abort_if_not_initialized(&repo).and_then(|_| standup::print(w, &repo, fmt, template.as_deref()))
+}
+
+fn painter(m: &ArgMatches) -> color::Painter {
+    let choice = match m.value_of("color") {
+        Some("always") => color::ColorChoice::Always,
+        Some("never") => color::ColorChoice::Never,
+        _ => color::ColorChoice::Auto,
+    };
+    color::Painter::new(choice.enabled(stdout().is_terminal()))
+}
+
+fn parse_template_arg(m: &ArgMatches) -> Result<Option<template::Template>, Error> {
+    m.value_of("template")
+        .map(template::Template::parse)
+        .transpose()
+}
+
+fn is_json_format(m: &ArgMatches) -> bool {
+    m.value_of("format") == Some("json")
+}
+
+fn parse_back_arg(m: &ArgMatches) -> Result<usize, Error> {
+    m.value_of("back")
+        .unwrap()
+        .parse::<usize>()
+        .map_err(|_| Error::InvalidArg("back must be an integer"))
 }
 
 fn parse_limit_arg(m: &ArgMatches) -> Result<usize, Error> {
@@ -222,18 +786,46 @@ fn parse_limit_arg(m: &ArgMatches) -> Result<usize, Error> {
     }
 }
 
+fn sync_cmd<W: Write>(w: &mut W, _m: &ArgMatches) -> Result<(), Error> {
+    let config = Config::load();
+    let repo = LogRepository::new(config.repo_dir());
+    abort_if_not_initialized(&repo).and_then(|()| {
+        if !git::is_repo(repo.path()) {
+            return Err(Error::GitFailed(format!(
+                "repository at {} is not a git repository; run `devlog init --git` to initialize it",
+                repo.path().display()
+            )));
+        }
+        let _lock = lock::lock(&config)?;
+        git::sync(repo.path())?;
+        writeln!(w, "Synced devlog repository at {:?}", repo.path())?;
+        Ok(())
+    })
+}
+
 fn tail_cmd<W: Write>(w: &mut W, m: &ArgMatches) -> Result<(), Error> {
     let limit = parse_limit_arg(m)?;
     let config = Config::load();
     let repo = LogRepository::new(config.repo_dir());
-    abort_if_not_initialized(w, &repo).and_then(|_| {
+    abort_if_not_initialized(&repo).and_then(|_| {
+        if is_json_format(m) {
+            return json::print_tail(w, &repo, limit);
+        } else if let Some(tmpl) = parse_template_arg(m)? {
+            return template::print_tail(w, &repo, limit, &tmpl);
+        }
+
+        let p = painter(m);
         let paths = repo.tail(limit)?;
         for (i, logpath) in paths.iter().enumerate() {
             if i > 0 {
-                write!(w, "\n~~~~~~~~~~~~~~~~~~~~~~\n")?;
+                write!(w, "\n{}\n", p.note("~~~~~~~~~~~~~~~~~~~~~~"))?;
             }
             let mut f = File::open(logpath.path())?;
-            copy(&mut f, w)?;
+            if p.enabled() {
+                p.copy_entry(BufReader::new(f), w)?;
+            } else {
+                copy(&mut f, w)?;
+            }
         }
         Ok(())
     })
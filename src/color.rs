@@ -0,0 +1,178 @@
+//! Highlight terminal output using ANSI color and style escape codes.
+
+use crate::task::{Task, TaskStatus};
+use std::env;
+use std::io::{BufRead, Write};
+
+const NO_COLOR_ENV_VAR: &str = "NO_COLOR";
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const CYAN: &str = "\x1b[36m";
+
+/// Controls whether output is highlighted.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Highlight output only if it is written to a terminal
+    /// and the `NO_COLOR` environment variable is not set.
+    Auto,
+
+    /// Always highlight output.
+    Always,
+
+    /// Never highlight output.
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether to highlight output written to a stream.
+    /// `is_terminal` indicates whether the stream is a terminal.
+    pub fn enabled(&self, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => is_terminal && !no_color_requested(),
+        }
+    }
+}
+
+// See https://no-color.org/
+fn no_color_requested() -> bool {
+    env::var_os(NO_COLOR_ENV_VAR).is_some_and(|v| !v.is_empty())
+}
+
+/// Applies styles to text, or leaves it unchanged if highlighting is disabled.
+#[derive(Debug, Copy, Clone)]
+pub struct Painter {
+    enabled: bool,
+}
+
+impl Painter {
+    /// Create a painter that highlights text if `enabled` is true.
+    pub fn new(enabled: bool) -> Painter {
+        Painter { enabled }
+    }
+
+    /// Whether the painter highlights text.
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Formats a task, colored according to its status.
+    pub fn task(&self, t: &Task) -> String {
+        self.paint(status_color(&t.status()), &t.to_string())
+    }
+
+    /// Formats a section header in bold.
+    pub fn header(&self, s: &str) -> String {
+        self.paint(BOLD, s)
+    }
+
+    /// Formats free-form notes dimmed.
+    pub fn note(&self, s: &str) -> String {
+        self.paint(DIM, s)
+    }
+
+    /// Copies the contents of a devlog entry file from `r` to `w`,
+    /// highlighting tasks by status and dimming everything else.
+    /// Invalid UTF-8 is replaced with U+FFFD REPLACEMENT CHARACTER.
+    pub fn copy_entry<R: BufRead, W: Write>(&self, r: R, w: &mut W) -> Result<(), std::io::Error> {
+        let mut in_code_block = false;
+        for bytes in r.split(b'\n') {
+            let mut bytes = bytes?;
+            if bytes.last() == Some(&b'\r') {
+                bytes.pop();
+            }
+            let line = String::from_utf8_lossy(&bytes);
+            let task = if in_code_block {
+                None
+            } else {
+                Task::from_string(&line)
+            };
+            if line.starts_with("```") {
+                in_code_block = !in_code_block;
+            }
+            match task {
+                Some(t) => writeln!(w, "{}", self.paint(status_color(&t.status()), &line))?,
+                None if line.is_empty() => writeln!(w)?,
+                None => writeln!(w, "{}", self.note(&line))?,
+            }
+        }
+        Ok(())
+    }
+
+    fn paint(&self, style: &str, s: &str) -> String {
+        if self.enabled {
+            format!("{}{}{}", style, s, RESET)
+        } else {
+            s.to_string()
+        }
+    }
+}
+
+fn status_color(s: &TaskStatus) -> &'static str {
+    match s {
+        TaskStatus::ToDo => YELLOW,
+        TaskStatus::Started => CYAN,
+        TaskStatus::Blocked => RED,
+        TaskStatus::Done => GREEN,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str;
+
+    #[test]
+    fn test_color_choice() {
+        assert!(ColorChoice::Always.enabled(false));
+        assert!(!ColorChoice::Never.enabled(true));
+        assert!(!ColorChoice::Auto.enabled(false));
+    }
+
+    #[test]
+    fn test_paint_disabled() {
+        let p = Painter::new(false);
+        let t = Task::new(TaskStatus::Done, "Foo");
+        assert_eq!(p.task(&t), "+ Foo");
+        assert_eq!(p.header("Done:"), "Done:");
+        assert_eq!(p.note("Note"), "Note");
+    }
+
+    #[test]
+    fn test_paint_enabled() {
+        let p = Painter::new(true);
+        let t = Task::new(TaskStatus::Blocked, "Foo");
+        assert_eq!(p.task(&t), "\x1b[31m- Foo\x1b[0m");
+        assert_eq!(p.header("Blocked:"), "\x1b[1mBlocked:\x1b[0m");
+        assert_eq!(p.note("Note"), "\x1b[2mNote\x1b[0m");
+    }
+
+    #[test]
+    fn test_copy_entry() {
+        let p = Painter::new(true);
+        let input = "* Foo\nNote\n\n```\n+ Code\n```\n";
+        let mut buf = Vec::new();
+        p.copy_entry(input.as_bytes(), &mut buf).unwrap();
+        assert_eq!(
+            str::from_utf8(&buf).unwrap(),
+            "\x1b[33m* Foo\x1b[0m\n\x1b[2mNote\x1b[0m\n\n\x1b[2m```\x1b[0m\n\x1b[2m+ Code\x1b[0m\n\x1b[2m```\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_copy_entry_invalid_utf8() {
+        let p = Painter::new(true);
+        let mut buf = Vec::new();
+        p.copy_entry(&b"* Fix \xff bug\r\n"[..], &mut buf).unwrap();
+        assert_eq!(
+            str::from_utf8(&buf).unwrap(),
+            "\x1b[33m* Fix \u{fffd} bug\x1b[0m\n"
+        );
+    }
+}
@@ -1,18 +1,33 @@
 //! Load configuration values from environment variables.
 
+use crate::plugin::DevlogPlugin;
 use dirs;
 use std::env;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 const DEVLOG_REPO_ENV_VAR: &str = "DEVLOG_REPO";
 const DEFAULT_HOME_DIR: &str = "devlogs";
 const DEVLOG_EDITOR_ENV_VAR: &str = "DEVLOG_EDITOR";
 const EDITOR_ENV_VAR: &str = "EDITOR";
 const DEFAULT_EDITOR: &str = "vim";
+const DEVLOG_GIT_AUTOCOMMIT_ENV_VAR: &str = "DEVLOG_GIT_AUTOCOMMIT";
+const DEVLOG_BEFORE_HOOK_VETO_ENV_VAR: &str = "DEVLOG_BEFORE_HOOK_VETO";
+const DEVLOG_HOOK_JSON_ENV_VAR: &str = "DEVLOG_HOOK_JSON";
+const DEVLOG_HOOK_TIMEOUT_ENV_VAR: &str = "DEVLOG_HOOK_TIMEOUT";
+const DEVLOG_HOOK_LOG_ENV_VAR: &str = "DEVLOG_HOOK_LOG";
+const DEVLOG_LOCK_TIMEOUT_ENV_VAR: &str = "DEVLOG_LOCK_TIMEOUT";
 
 pub struct Config {
     repo_dir: PathBuf,
     editor_prog: String,
+    git_autocommit: bool,
+    before_hook_veto: bool,
+    hook_json: bool,
+    hook_timeout: Option<Duration>,
+    hook_log: bool,
+    lock_timeout: Option<Duration>,
+    plugins: Vec<Box<dyn DevlogPlugin>>,
 }
 
 impl Config {
@@ -22,6 +37,13 @@ impl Config {
         Config {
             repo_dir: repo_dir.to_path_buf(),
             editor_prog: editor_prog.to_string(),
+            git_autocommit: true,
+            before_hook_veto: true,
+            hook_json: false,
+            hook_timeout: None,
+            hook_log: false,
+            lock_timeout: None,
+            plugins: Vec::new(),
         }
     }
 
@@ -38,9 +60,29 @@ impl Config {
             .or_else(|_| env::var(EDITOR_ENV_VAR))
             .unwrap_or_else(|_| DEFAULT_EDITOR.to_string());
 
+        // These are enabled unless explicitly disabled.
+        let git_autocommit = env_flag(DEVLOG_GIT_AUTOCOMMIT_ENV_VAR, true);
+        let before_hook_veto = env_flag(DEVLOG_BEFORE_HOOK_VETO_ENV_VAR, true);
+        let hook_json = env_flag(DEVLOG_HOOK_JSON_ENV_VAR, false);
+        let hook_log = env_flag(DEVLOG_HOOK_LOG_ENV_VAR, false);
+
+        // Hooks run without a time limit unless this is set to a positive number of seconds.
+        let hook_timeout = env_secs(DEVLOG_HOOK_TIMEOUT_ENV_VAR);
+
+        // Commands fail right away if another process holds the repository lock,
+        // unless this is set to a positive number of seconds to wait.
+        let lock_timeout = env_secs(DEVLOG_LOCK_TIMEOUT_ENV_VAR);
+
         Config {
             repo_dir,
             editor_prog,
+            git_autocommit,
+            before_hook_veto,
+            hook_json,
+            hook_timeout,
+            hook_log,
+            lock_timeout,
+            plugins: Vec::new(),
         }
     }
 
@@ -55,6 +97,97 @@ impl Config {
     pub fn editor_prog(&self) -> &str {
         &self.editor_prog
     }
+
+    /// Whether to commit all changes in the repository directory after edit, add and rollover
+    /// if the repository is a git repository. Defaults to true; set $DEVLOG_GIT_AUTOCOMMIT to "0" or "false" to disable.
+    pub fn git_autocommit(&self) -> bool {
+        self.git_autocommit
+    }
+
+    /// Whether a before-* hook that exits with an unsuccessful status aborts the operation.
+    /// Defaults to true; set $DEVLOG_BEFORE_HOOK_VETO to "0" or "false" to only print a warning.
+    pub fn before_hook_veto(&self) -> bool {
+        self.before_hook_veto
+    }
+
+    /// Sets whether a failing before-* hook aborts the operation.
+    pub fn set_before_hook_veto(&mut self, veto: bool) {
+        self.before_hook_veto = veto;
+    }
+
+    /// Whether to write a JSON description of the operation to each hook's stdin.
+    /// Defaults to false; set $DEVLOG_HOOK_JSON to "1" or "true" to enable.
+    pub fn hook_json(&self) -> bool {
+        self.hook_json
+    }
+
+    /// Sets whether to write a JSON description of the operation to each hook's stdin.
+    pub fn set_hook_json(&mut self, enabled: bool) {
+        self.hook_json = enabled;
+    }
+
+    /// How long each hook may run before it is killed, or `None` for no limit.
+    /// Defaults to no limit; set $DEVLOG_HOOK_TIMEOUT to a number of seconds to enable.
+    pub fn hook_timeout(&self) -> Option<Duration> {
+        self.hook_timeout
+    }
+
+    /// Sets how long each hook may run before it is killed.
+    pub fn set_hook_timeout(&mut self, timeout: Option<Duration>) {
+        self.hook_timeout = timeout;
+    }
+
+    /// Whether to append the output of hooks to the hook log in the repository
+    /// instead of printing it to the terminal.
+    /// Defaults to false; set $DEVLOG_HOOK_LOG to "1" or "true" to enable.
+    pub fn hook_log(&self) -> bool {
+        self.hook_log
+    }
+
+    /// Sets whether to append the output of hooks to the hook log in the repository.
+    pub fn set_hook_log(&mut self, enabled: bool) {
+        self.hook_log = enabled;
+    }
+
+    /// How long to wait for another devlog process to release the repository lock,
+    /// or `None` to fail right away.
+    /// Defaults to `None`; set $DEVLOG_LOCK_TIMEOUT to a number of seconds to wait.
+    pub fn lock_timeout(&self) -> Option<Duration> {
+        self.lock_timeout
+    }
+
+    /// Sets how long to wait for another devlog process to release the repository lock.
+    pub fn set_lock_timeout(&mut self, timeout: Option<Duration>) {
+        self.lock_timeout = timeout;
+    }
+
+    /// Registers an in-process plugin, which is notified of the same events as hooks.
+    /// Plugins are notified in registration order.
+    pub fn register_plugin(&mut self, plugin: Box<dyn DevlogPlugin>) {
+        self.plugins.push(plugin);
+    }
+
+    /// The registered plugins, in registration order.
+    pub fn plugins(&self) -> &[Box<dyn DevlogPlugin>] {
+        &self.plugins
+    }
+}
+
+// Parses a boolean environment variable, where "0", "false", "no", and "off" mean false
+// and any other value means true.
+fn env_flag(name: &str, default: bool) -> bool {
+    env::var(name)
+        .map(|s| !matches!(s.to_lowercase().as_str(), "0" | "false" | "no" | "off"))
+        .unwrap_or(default)
+}
+
+// Parses an environment variable as a positive number of seconds.
+fn env_secs(name: &str) -> Option<Duration> {
+    env::var(name)
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok())
+        .filter(|&secs| secs > 0)
+        .map(Duration::from_secs)
 }
 
 fn default_repo_dir() -> String {
@@ -0,0 +1,201 @@
+//! Export the task history of a devlog repository as CSV (RFC 4180).
+//!
+//! By default, each row is one occurrence of a task in an entry, so a task
+//! rolled over through several entries appears once per entry.
+//! Collapsed output instead has one row per distinct task content,
+//! with the entries where it was first and last seen and its most recent status.
+
+use crate::error::Error;
+use crate::file::LogFile;
+use crate::repository::LogRepository;
+use crate::task::Task;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::io::Write;
+
+const HEADER: [&str; 6] = ["seq_num", "date", "status", "content", "tags", "line"];
+
+const COLLAPSED_HEADER: [&str; 7] = [
+    "first_seq_num",
+    "first_date",
+    "last_seq_num",
+    "last_date",
+    "status",
+    "content",
+    "tags",
+];
+
+struct Occurrence {
+    seq_num: usize,
+    date: NaiveDate,
+    line_num: usize,
+    task: Task,
+}
+
+/// Writes every task in every entry of the repository as CSV, oldest entry first.
+/// If `collapse` is true, tasks with the same content are written as a single row.
+/// Returns the number of rows written, excluding the header.
+pub fn export<W: Write>(w: &mut W, repo: &LogRepository, collapse: bool) -> Result<usize, Error> {
+    let occurrences = load_occurrences(repo)?;
+    if collapse {
+        write_collapsed(w, &occurrences)
+    } else {
+        write_occurrences(w, &occurrences)
+    }
+}
+
+fn load_occurrences(repo: &LogRepository) -> Result<Vec<Occurrence>, Error> {
+    let mut paths = repo.list()?;
+    paths.sort();
+    let mut occurrences = Vec::new();
+    for logpath in paths.iter() {
+        let date = logpath.date()?;
+        let f = LogFile::load_strict(logpath.path())?;
+        for (line_num, task) in f.tasks_with_line_nums() {
+            occurrences.push(Occurrence {
+                seq_num: logpath.seq_num(),
+                date,
+                line_num,
+                task: task.clone(),
+            });
+        }
+    }
+    Ok(occurrences)
+}
+
+fn write_occurrences<W: Write>(w: &mut W, occurrences: &[Occurrence]) -> Result<usize, Error> {
+    write_record(w, &HEADER)?;
+    for o in occurrences.iter() {
+        write_record(
+            w,
+            &[
+                &o.seq_num.to_string(),
+                &o.date.to_string(),
+                o.task.status().name(),
+                o.task.content(),
+                &o.task.tags().join(" "),
+                &o.line_num.to_string(),
+            ],
+        )?;
+    }
+    Ok(occurrences.len())
+}
+
+fn write_collapsed<W: Write>(w: &mut W, occurrences: &[Occurrence]) -> Result<usize, Error> {
+    // Rows are ordered by when each task was first seen.
+    let mut rows: Vec<(&Occurrence, &Occurrence)> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for o in occurrences.iter() {
+        match index.get(o.task.content()) {
+            Some(&i) => rows[i].1 = o,
+            None => {
+                index.insert(o.task.content(), rows.len());
+                rows.push((o, o));
+            }
+        }
+    }
+
+    write_record(w, &COLLAPSED_HEADER)?;
+    for (first, last) in rows.iter() {
+        write_record(
+            w,
+            &[
+                &first.seq_num.to_string(),
+                &first.date.to_string(),
+                &last.seq_num.to_string(),
+                &last.date.to_string(),
+                last.task.status().name(),
+                last.task.content(),
+                &last.task.tags().join(" "),
+            ],
+        )?;
+    }
+    Ok(rows.len())
+}
+
+fn write_record<W: Write>(w: &mut W, fields: &[&str]) -> Result<(), Error> {
+    let quoted: Vec<String> = fields.iter().map(|f| quote(f)).collect();
+    // RFC 4180 records end with CRLF.
+    write!(w, "{}\r\n", quoted.join(","))?;
+    Ok(())
+}
+
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::LogPath;
+    use std::fs::OpenOptions;
+    use std::str;
+    use tempfile::tempdir;
+
+    fn write_entry(p: &LogPath, content: &str) {
+        let mut f = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(p.path())
+            .unwrap();
+        write!(f, "{}", content).unwrap();
+    }
+
+    fn init_repo(dir: &std::path::Path) -> (LogRepository, String) {
+        let repo = LogRepository::new(dir);
+        let first = repo.init().unwrap();
+        let second = first.next().unwrap();
+        write_entry(&first, "* Fix \"bug\", again #backend\n^ Write docs\n");
+        write_entry(&second, "Notes\n+ Fix \"bug\", again #backend\n");
+        let date = first.date().unwrap().to_string();
+        (repo, date)
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("plain"), "plain");
+        assert_eq!(quote("a,b"), "\"a,b\"");
+        assert_eq!(quote("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(quote("a\nb"), "\"a\nb\"");
+    }
+
+    #[test]
+    fn test_export() {
+        let dir = tempdir().unwrap();
+        let (repo, d) = init_repo(dir.path());
+        let mut buf = Vec::new();
+        assert_eq!(export(&mut buf, &repo, false).unwrap(), 3);
+        assert_eq!(
+            str::from_utf8(&buf).unwrap(),
+            format!(
+                "seq_num,date,status,content,tags,line\r\n\
+                 1,{d},todo,\"Fix \"\"bug\"\", again #backend\",backend,1\r\n\
+                 1,{d},started,Write docs,,2\r\n\
+                 2,{d},done,\"Fix \"\"bug\"\", again #backend\",backend,2\r\n",
+                d = d
+            )
+        );
+    }
+
+    #[test]
+    fn test_export_collapsed() {
+        let dir = tempdir().unwrap();
+        let (repo, d) = init_repo(dir.path());
+        let mut buf = Vec::new();
+        assert_eq!(export(&mut buf, &repo, true).unwrap(), 2);
+        assert_eq!(
+            str::from_utf8(&buf).unwrap(),
+            format!(
+                "first_seq_num,first_date,last_seq_num,last_date,status,content,tags\r\n\
+                 1,{d},2,{d},done,\"Fix \"\"bug\"\", again #backend\",backend\r\n\
+                 1,{d},1,{d},started,Write docs,\r\n",
+                d = d
+            )
+        );
+    }
+}
@@ -2,23 +2,134 @@
 
 use crate::config::Config;
 use crate::error::Error;
-use crate::hook::{execute_hook, HookType};
+use crate::file::{LogFile, ParseMode};
+use crate::git;
+use crate::hook::{execute_hook, HookContext, HookType};
+use crate::json;
+use crate::lock::{self, RepoLock};
+use crate::path::LogPath;
+use crate::plugin;
+use crate::task::{Task, TaskStatus};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::ffi::OsStr;
 use std::io::Write;
 use std::path::Path;
 use std::process::Command;
 
 /// Opens the specified file in a text editor program.
-/// If available, the before-edit and after-edit hooks are invoked.
+/// If available, the before-edit and after-edit hooks are invoked,
+/// followed by the task-completed hook for each task the user marked as done.
+/// Registered plugins are notified of the same events if the file is a devlog entry.
+/// Afterwards, changes are committed if the repository is a git repository with auto-commit enabled.
+/// The repository is locked until the editor and all hooks have exited.
 pub fn open<W: Write>(w: &mut W, config: &Config, path: &Path) -> Result<(), Error> {
-    execute_hook(w, config, &HookType::BeforeEdit, &[path.as_os_str()])?;
+    let lock = lock::lock(config)?;
+    open_with_lock(w, config, &lock, path)
+}
+
+/// Same as `open`, but for a caller that already holds the repository lock.
+pub fn open_with_lock<W: Write>(
+    w: &mut W,
+    config: &Config,
+    _lock: &RepoLock,
+    path: &Path,
+) -> Result<(), Error> {
+    let logpath = LogPath::from_path(path.to_path_buf());
+    let seq_num = logpath.as_ref().map(LogPath::seq_num);
+    let ctx = HookContext::new("edit", seq_num);
+    execute_hook(w, config, &HookType::BeforeEdit, &[path.as_os_str()], &ctx)?;
+    if let Some(p) = &logpath {
+        plugin::notify(w, config, &HookType::BeforeEdit, |plugin| {
+            plugin.before_edit(p)
+        })?;
+    }
+
+    let before = load_tasks(path)?;
     open_in_editor(w, config, path)?;
-    execute_hook(w, config, &HookType::AfterEdit, &[path.as_os_str()])?;
+    let after = load_tasks(path)?;
+
+    let ctx = if config.hook_json() {
+        ctx.with_tasks(task_values(logpath.as_ref(), &after))
+    } else {
+        ctx
+    };
+    execute_hook(w, config, &HookType::AfterEdit, &[path.as_os_str()], &ctx)?;
+    if let Some(p) = &logpath {
+        let tasks: Vec<Task> = after.iter().map(|(_, t)| t.clone()).collect();
+        plugin::notify(w, config, &HookType::AfterEdit, |plugin| {
+            plugin.after_edit(p, &tasks)
+        })?;
+    }
+
+    for (n, t) in completed_tasks(&before, &after) {
+        let ctx = HookContext::new("edit", seq_num)
+            .with_tasks(task_values(logpath.as_ref(), &[(*n, t.clone())]));
+        let args = [path.as_os_str(), OsStr::new(t.content())];
+        execute_hook(w, config, &HookType::TaskCompleted, &args, &ctx)?;
+        if let Some(p) = &logpath {
+            plugin::notify(w, config, &HookType::TaskCompleted, |plugin| {
+                plugin.task_completed(p, t)
+            })?;
+        }
+    }
+
+    git::autocommit(w, config, &format!("Edit {}", file_name(path)))?;
     Ok(())
 }
 
+// Returns the tasks in the file with their line numbers, or no tasks if the file does not exist.
+// The file is decoded lossily so that invalid UTF-8 does not prevent the hooks from running.
+fn load_tasks(path: &Path) -> Result<Vec<(usize, Task)>, Error> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let f = LogFile::load_with_mode(path, ParseMode::Lossy)?;
+    Ok(f.tasks_with_line_nums()
+        .map(|(n, t)| (n, t.clone()))
+        .collect())
+}
+
+fn task_values(logpath: Option<&LogPath>, tasks: &[(usize, Task)]) -> Vec<Value> {
+    match logpath {
+        Some(logpath) => tasks
+            .iter()
+            .map(|(n, t)| json::task_value(logpath, *n, t))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+// Tasks are matched by content, so a task counts as completed if it is done after editing
+// and a task with the same content was incomplete before.
+fn completed_tasks<'a>(
+    before: &[(usize, Task)],
+    after: &'a [(usize, Task)],
+) -> Vec<&'a (usize, Task)> {
+    let incomplete: HashSet<&str> = before
+        .iter()
+        .filter(|(_, t)| t.status() != TaskStatus::Done)
+        .map(|(_, t)| t.content())
+        .collect();
+    after
+        .iter()
+        .filter(|(_, t)| t.status() == TaskStatus::Done && incomplete.contains(t.content()))
+        .collect()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .to_string()
+}
+
 fn open_in_editor<W: Write>(w: &mut W, config: &Config, path: &Path) -> Result<(), Error> {
     let prog = config.editor_prog();
-    let status = Command::new(prog).arg(&path).status()?;
+    let status = Command::new(prog)
+        .arg(path)
+        .status()
+        .map_err(Error::at_path("run editor", Path::new(prog)))?;
 
     if status.success() {
         Ok(())
@@ -36,3 +147,67 @@ fn open_in_editor<W: Write>(w: &mut W, config: &Config, path: &Path) -> Result<(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::LogRepository;
+    use std::fs::{create_dir_all, read_to_string, set_permissions, File, Permissions};
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::tempdir;
+
+    fn create_script(p: &Path, content: &str) {
+        let mut f = File::create(p).unwrap();
+        write!(f, "#!/usr/bin/env sh\n{}", content).unwrap();
+        drop(f);
+        set_permissions(p, Permissions::from_mode(0o555)).unwrap();
+    }
+
+    #[test]
+    fn test_completed_tasks() {
+        let before = [
+            (1, Task::new(TaskStatus::ToDo, "a")),
+            (2, Task::new(TaskStatus::Started, "b")),
+            (3, Task::new(TaskStatus::Done, "c")),
+            (4, Task::new(TaskStatus::Blocked, "d")),
+        ];
+        let after = [
+            (1, Task::new(TaskStatus::Done, "a")),
+            (2, Task::new(TaskStatus::Started, "b")),
+            (3, Task::new(TaskStatus::Done, "c")),
+            (4, Task::new(TaskStatus::Done, "e")),
+            (5, Task::new(TaskStatus::Done, "d")),
+        ];
+        let completed: Vec<usize> = completed_tasks(&before, &after)
+            .iter()
+            .map(|(n, _)| *n)
+            .collect();
+        assert_eq!(completed, vec![1, 5]);
+    }
+
+    #[test]
+    fn test_task_completed_hook() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let logpath = repo.init().unwrap();
+
+        let editor = dir.path().join("editor.sh");
+        create_script(&editor, "sed -i 's/^^ Use a caret/+ Use a caret/' \"$1\"\n");
+        let hook_dir = dir.path().join("hooks");
+        create_dir_all(&hook_dir).unwrap();
+        create_script(
+            &hook_dir.join(HookType::TaskCompleted.name()),
+            "echo \"$DEVLOG_SEQ_NUM $2\" >> \"$DEVLOG_REPO/completed.out\"\n",
+        );
+
+        let config = Config::new(dir.path(), editor.to_str().unwrap());
+        let mut out = Vec::new();
+        open(&mut out, &config, logpath.path()).unwrap();
+
+        let completed = read_to_string(dir.path().join("completed.out")).unwrap();
+        assert_eq!(
+            completed,
+            "1 Use a caret symbol (^) for tasks that are in progress.\n"
+        );
+    }
+}
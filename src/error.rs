@@ -1,6 +1,41 @@
 //! Error type returned by the devlog library.
+//!
+//! Each class of error has a distinct process exit code for the command-line app
+//! (see `Error::exit_code`), so scripts can tell, for example, an uninitialized repository
+//! apart from a failed hook.
 
+use std::error;
+use std::fmt;
 use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Exit code for errors without a more specific code, such as I/O errors.
+pub const EXIT_FAILURE: i32 = 1;
+
+/// Exit code for invalid command-line arguments.
+pub const EXIT_INVALID_ARG: i32 = 2;
+
+/// Exit code when the repository has not been initialized.
+pub const EXIT_NOT_INITIALIZED: i32 = 3;
+
+/// Exit code when a hook fails.
+pub const EXIT_HOOK_FAILED: i32 = 4;
+
+/// Exit code when a devlog entry file cannot be parsed.
+pub const EXIT_PARSE: i32 = 5;
+
+/// Exit code when a git command fails.
+pub const EXIT_GIT_FAILED: i32 = 6;
+
+/// Exit code when another devlog process holds the repository lock.
+pub const EXIT_REPO_LOCKED: i32 = 7;
+
+/// Exit code when `devlog lint` finds problems in a devlog entry file.
+pub const EXIT_LINT_FAILED: i32 = 8;
+
+/// Exit code when a new devlog entry file would replace an existing one.
+pub const EXIT_ENTRY_EXISTS: i32 = 9;
 
 #[derive(Debug)]
 pub enum Error {
@@ -11,12 +46,291 @@ pub enum Error {
     /// so no more can be created.
     LogFileLimitExceeded,
 
+    /// The repository at the path has not been initialized.
+    NotInitialized(PathBuf),
+
+    /// A hook exited with an unsuccessful status.
+    /// `code` is `None` if the hook was terminated by a signal.
+    HookFailed { hook: String, code: Option<i32> },
+
+    /// A hook ran longer than `Config::hook_timeout` and was killed.
+    HookTimedOut { hook: String, timeout: Duration },
+
+    /// A git command exited with an unsuccessful status.
+    /// Contains the command and the error output from git.
+    GitFailed(String),
+
+    /// A devlog entry file could not be parsed.
+    Parse(ParseError),
+
+    /// Another devlog process holds the lock on the repository at the path.
+    RepoLocked(PathBuf),
+
+    /// Linting found problems in the devlog entry file at the path.
+    LintFailed { path: PathBuf, count: usize },
+
+    /// A new devlog entry file could not be created because the path already exists.
+    EntryExists(PathBuf),
+
+    /// An I/O operation on the path failed.
+    /// `operation` describes what devlog was doing, e.g. "open" or "run hook".
+    PathIOError {
+        operation: &'static str,
+        path: PathBuf,
+        source: IOError,
+    },
+
     /// Wraps `io::Error`
     IOError(IOError),
 }
 
+impl Error {
+    /// Returns a function that wraps an `io::Error` with the operation and path that caused it,
+    /// for use with `map_err`.
+    pub fn at_path(operation: &'static str, path: &Path) -> impl FnOnce(IOError) -> Error {
+        let path = path.to_path_buf();
+        move |source| Error::PathIOError {
+            operation,
+            path,
+            source,
+        }
+    }
+
+    /// Returns the exit code the command-line app uses for this error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::InvalidArg(_) => EXIT_INVALID_ARG,
+            Error::NotInitialized(_) => EXIT_NOT_INITIALIZED,
+            Error::HookFailed { .. } | Error::HookTimedOut { .. } => EXIT_HOOK_FAILED,
+            Error::Parse(_) => EXIT_PARSE,
+            Error::GitFailed(_) => EXIT_GIT_FAILED,
+            Error::RepoLocked(_) => EXIT_REPO_LOCKED,
+            Error::LintFailed { .. } => EXIT_LINT_FAILED,
+            Error::EntryExists(_) => EXIT_ENTRY_EXISTS,
+            Error::LogFileLimitExceeded | Error::PathIOError { .. } | Error::IOError(_) => {
+                EXIT_FAILURE
+            }
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidArg(msg) => write!(f, "invalid argument: {}", msg),
+            Error::LogFileLimitExceeded => write!(
+                f,
+                "the repository contains the maximum number of devlog entries"
+            ),
+            Error::NotInitialized(path) => write!(
+                f,
+                "repository at {} has not been initialized; run `devlog init` to initialize it",
+                path.display()
+            ),
+            Error::HookFailed {
+                hook,
+                code: Some(code),
+            } => write!(f, "{} hook exited with status {}", hook, code),
+            Error::HookFailed { hook, code: None } => {
+                write!(f, "{} hook was terminated by a signal", hook)
+            }
+            Error::HookTimedOut { hook, timeout } => {
+                write!(
+                    f,
+                    "{} hook timed out after {:?} and was killed",
+                    hook, timeout
+                )
+            }
+            Error::GitFailed(msg) => write!(f, "{}", msg),
+            Error::Parse(err) => write!(f, "{}", err),
+            Error::RepoLocked(path) => write!(
+                f,
+                "repository at {} is in use by another devlog process; try again later, or set DEVLOG_LOCK_TIMEOUT to wait",
+                path.display()
+            ),
+            Error::LintFailed { path, count } => {
+                write!(f, "found {} problem(s) in {}", count, path.display())
+            }
+            Error::EntryExists(path) => write!(
+                f,
+                "devlog entry {} already exists; another devlog process may have rolled over",
+                path.display()
+            ),
+            Error::PathIOError {
+                operation,
+                path,
+                source,
+            } => write!(f, "could not {} {}: {}", operation, path.display(), source),
+            Error::IOError(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Parse(err) => Some(err),
+            Error::PathIOError { source, .. } => Some(source),
+            Error::IOError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
 impl From<IOError> for Error {
     fn from(err: IOError) -> Error {
         Error::IOError(err)
     }
 }
+
+/// Describes where and why a devlog entry file could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    path: PathBuf,
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl ParseError {
+    /// Creates a parse error at the specified line and column, both starting from one.
+    pub fn new(path: &Path, line: usize, column: usize, message: &str) -> ParseError {
+        ParseError {
+            path: path.to_path_buf(),
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+
+    /// Returns the path of the file that could not be parsed.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the line number of the error, starting from one.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the column of the error in characters, starting from one.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Returns a description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.path.display(),
+            self.line,
+            self.column,
+            self.message
+        )
+    }
+}
+
+impl error::Error for ParseError {}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Error {
+        Error::Parse(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+    use std::io::ErrorKind;
+
+    #[test]
+    fn test_display_path_io_error() {
+        let err = Error::at_path("open", Path::new("/tmp/x.devlog"))(IOError::new(
+            ErrorKind::NotFound,
+            "not found",
+        ));
+        assert_eq!(err.to_string(), "could not open /tmp/x.devlog: not found");
+        assert!(err.source().is_some());
+        assert_eq!(err.exit_code(), EXIT_FAILURE);
+    }
+
+    #[test]
+    fn test_display_hook_failed() {
+        let err = Error::HookFailed {
+            hook: "before-edit".to_string(),
+            code: Some(3),
+        };
+        assert_eq!(err.to_string(), "before-edit hook exited with status 3");
+        assert!(err.source().is_none());
+        assert_eq!(err.exit_code(), EXIT_HOOK_FAILED);
+    }
+
+    #[test]
+    fn test_display_hook_timed_out() {
+        let err = Error::HookTimedOut {
+            hook: "after-edit".to_string(),
+            timeout: Duration::from_secs(5),
+        };
+        assert_eq!(
+            err.to_string(),
+            "after-edit hook timed out after 5s and was killed"
+        );
+        assert_eq!(err.exit_code(), EXIT_HOOK_FAILED);
+    }
+
+    #[test]
+    fn test_display_parse_error() {
+        let err = Error::from(ParseError::new(
+            Path::new("x.devlog"),
+            2,
+            5,
+            "invalid UTF-8",
+        ));
+        assert_eq!(err.to_string(), "x.devlog:2:5: invalid UTF-8");
+        assert!(err.source().is_some());
+        assert_eq!(err.exit_code(), EXIT_PARSE);
+    }
+
+    #[test]
+    fn test_display_lint_failed() {
+        let err = Error::LintFailed {
+            path: PathBuf::from("x.devlog"),
+            count: 2,
+        };
+        assert_eq!(err.to_string(), "found 2 problem(s) in x.devlog");
+        assert_eq!(err.exit_code(), EXIT_LINT_FAILED);
+    }
+
+    #[test]
+    fn test_exit_codes_are_distinct() {
+        let errors = [
+            Error::InvalidArg("x"),
+            Error::NotInitialized(PathBuf::new()),
+            Error::HookFailed {
+                hook: "x".to_string(),
+                code: None,
+            },
+            Error::Parse(ParseError::new(Path::new("x"), 1, 1, "x")),
+            Error::GitFailed("x".to_string()),
+            Error::RepoLocked(PathBuf::new()),
+            Error::LintFailed {
+                path: PathBuf::new(),
+                count: 1,
+            },
+            Error::EntryExists(PathBuf::new()),
+            Error::LogFileLimitExceeded,
+        ];
+        let mut codes: Vec<i32> = errors.iter().map(|e| e.exit_code()).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+    }
+}
@@ -1,24 +1,67 @@
 //! Load and parse a devlog entry file.
 
+use crate::error::{Error, ParseError};
 use crate::task::Task;
-use std::fs::File;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
 use std::io::Error as IOError;
-use std::io::{BufRead, BufReader};
+use std::io::ErrorKind;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 
+/// Controls how a devlog entry file that is not valid UTF-8 is parsed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Fail with `Error::Parse` at the first line that is not valid UTF-8.
+    Strict,
+
+    /// Replace invalid UTF-8 sequences with U+FFFD REPLACEMENT CHARACTER.
+    /// Tasks on lines with invalid bytes are kept, but their content may differ from the file.
+    Lossy,
+}
+
 /// Represents a devlog entry file.
+/// With the `serde` feature, it is serialized as an object with the fields `tasks`
+/// and `line_nums`, where `line_nums[i]` is the line number of `tasks[i]`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct LogFile {
     tasks: Vec<Task>,
+    line_nums: Vec<usize>,
 }
 
 impl LogFile {
-    /// Loads and parses the devlog entry file at `path`
+    /// Loads and parses the devlog entry file at `path`.
+    /// Fails with an error of kind `ErrorKind::InvalidData` if the file is not valid UTF-8.
+    /// Use `load_strict` to get the line and column of the invalid bytes as an `Error::Parse`,
+    /// or `load_with_mode` to decode such files lossily instead.
     pub fn load(path: &Path) -> Result<LogFile, IOError> {
-        let f = File::open(path)?;
+        LogFile::load_strict(path).map_err(|err| match err {
+            Error::IOError(err) => err,
+            Error::PathIOError { ref source, .. } => IOError::new(source.kind(), err.to_string()),
+            Error::Parse(err) => IOError::new(ErrorKind::InvalidData, err),
+            err => IOError::other(err.to_string()),
+        })
+    }
+
+    /// Loads and parses the devlog entry file at `path`.
+    /// Fails with `Error::Parse` at the first line that is not valid UTF-8.
+    pub fn load_strict(path: &Path) -> Result<LogFile, Error> {
+        LogFile::load_with_mode(path, ParseMode::Strict)
+    }
+
+    /// Loads and parses the devlog entry file at `path`,
+    /// handling invalid UTF-8 as specified by `mode`.
+    pub fn load_with_mode(path: &Path, mode: ParseMode) -> Result<LogFile, Error> {
+        let f = File::open(path).map_err(Error::at_path("open", path))?;
         let r = BufReader::new(f);
         let mut tasks = Vec::new();
+        let mut line_nums = Vec::new();
         let mut start_free_form = false;
-        for line in r.lines().flatten() {
+        for (i, bytes) in r.split(b'\n').enumerate() {
+            let bytes = bytes.map_err(Error::at_path("read", path))?;
+            let line = decode_line(bytes, mode)
+                .map_err(|col| ParseError::new(path, i + 1, col, "invalid UTF-8"))?;
             // if the line starts with ``` then assume its
             // a code block, and therefore exempt from devlog
             // formatting rules
@@ -31,16 +74,74 @@ impl LogFile {
                 continue;
             }
             if let Some(task) = Task::from_string(&line) {
-                tasks.push(task)
+                tasks.push(task);
+                line_nums.push(i + 1);
             }
         }
-        Ok(LogFile { tasks })
+        Ok(LogFile { tasks, line_nums })
     }
 
     /// Returns the tasks contained in the devlog entry file.
     pub fn tasks(&self) -> &[Task] {
         &self.tasks
     }
+
+    /// Returns the tasks contained in the devlog entry file,
+    /// each paired with its line number in the file (starting from one).
+    pub fn tasks_with_line_nums(&self) -> impl Iterator<Item = (usize, &Task)> {
+        self.line_nums.iter().copied().zip(self.tasks.iter())
+    }
+}
+
+// Decodes a line without its trailing newline or carriage return.
+// In strict mode, fails with the column of the first invalid character.
+fn decode_line(mut bytes: Vec<u8>, mode: ParseMode) -> Result<String, usize> {
+    if bytes.last() == Some(&b'\r') {
+        bytes.pop();
+    }
+    match String::from_utf8(bytes) {
+        Ok(s) => Ok(s),
+        Err(err) if mode == ParseMode::Lossy => {
+            Ok(String::from_utf8_lossy(err.as_bytes()).to_string())
+        }
+        Err(err) => {
+            let valid = &err.as_bytes()[..err.utf8_error().valid_up_to()];
+            // The valid prefix is known to be UTF-8, so this never replaces anything.
+            Err(String::from_utf8_lossy(valid).chars().count() + 1)
+        }
+    }
+}
+
+/// Appends tasks to the end of the devlog entry file at `path`, one per line.
+/// If the file does not end with a newline, one is added before the first task.
+pub fn append_tasks(path: &Path, tasks: &[Task]) -> Result<(), IOError> {
+    let mut f = open_for_append(path)?;
+    for t in tasks {
+        writeln!(f, "{}", t)?;
+    }
+    Ok(())
+}
+
+/// Appends a fence ("```") to the end of the devlog entry file at `path`,
+/// which closes a code block left open at the end of the file.
+/// If the file does not end with a newline, one is added before the fence.
+pub fn close_code_block(path: &Path) -> Result<(), IOError> {
+    let mut f = open_for_append(path)?;
+    writeln!(f, "```")
+}
+
+// Opens the file for appending, adding a newline if the file does not end with one.
+fn open_for_append(path: &Path) -> Result<File, IOError> {
+    let mut f = OpenOptions::new().read(true).append(true).open(path)?;
+    if f.metadata()?.len() > 0 {
+        let mut last = [0; 1];
+        f.seek(SeekFrom::End(-1))?;
+        f.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            writeln!(f)?;
+        }
+    }
+    Ok(f)
 }
 
 #[cfg(test)]
@@ -58,6 +159,7 @@ mod tests {
         let mut f = OpenOptions::new()
             .write(true)
             .create(true)
+            .truncate(true)
             .open(&p)
             .unwrap();
         writeln!(f, "+ DONE").unwrap();
@@ -66,11 +168,109 @@ mod tests {
         writeln!(f, "COMMENT").unwrap();
 
         let lf = LogFile::load(&p).unwrap();
-        let expected = vec![
+        let expected = [
             Task::new(TaskStatus::Done, "DONE"),
             Task::new(TaskStatus::Blocked, "BLOCKED"),
             Task::new(TaskStatus::ToDo, "INCOMPLETE"),
         ];
         assert_eq!(lf.tasks(), &expected[..]);
     }
+
+    #[test]
+    fn test_load_line_nums() {
+        let dir = tempdir().unwrap();
+        let p = dir.path().join("testlog");
+        let mut f = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&p)
+            .unwrap();
+        writeln!(f, "COMMENT").unwrap();
+        writeln!(f, "* FIRST").unwrap();
+        writeln!(f, "```").unwrap();
+        writeln!(f, "* IN CODE BLOCK").unwrap();
+        writeln!(f, "```").unwrap();
+        writeln!(f, "+ SECOND").unwrap();
+
+        let lf = LogFile::load(&p).unwrap();
+        let line_nums: Vec<usize> = lf.tasks_with_line_nums().map(|(n, _)| n).collect();
+        assert_eq!(line_nums, vec![2, 6]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_roundtrip() {
+        let dir = tempdir().unwrap();
+        let p = dir.path().join("testlog");
+        let mut f = File::create(&p).unwrap();
+        writeln!(f, "COMMENT").unwrap();
+        writeln!(f, "^ STARTED").unwrap();
+
+        let lf = LogFile::load(&p).unwrap();
+        let s = serde_json::to_string(&lf).unwrap();
+        assert_eq!(
+            s,
+            r#"{"tasks":[{"status":"started","content":"STARTED"}],"line_nums":[2]}"#
+        );
+        let parsed: LogFile = serde_json::from_str(&s).unwrap();
+        assert_eq!(parsed.tasks(), lf.tasks());
+    }
+
+    #[test]
+    fn test_append_tasks() {
+        let dir = tempdir().unwrap();
+        let p = dir.path().join("testlog");
+        let mut f = File::create(&p).unwrap();
+        write!(f, "* FIRST\nno trailing newline").unwrap();
+
+        append_tasks(&p, &[Task::new(TaskStatus::Done, "SECOND")]).unwrap();
+        append_tasks(&p, &[Task::new(TaskStatus::Blocked, "THIRD")]).unwrap();
+
+        let lf = LogFile::load(&p).unwrap();
+        let line_nums: Vec<usize> = lf.tasks_with_line_nums().map(|(n, _)| n).collect();
+        assert_eq!(line_nums, vec![1, 3, 4]);
+        assert_eq!(lf.tasks()[2], Task::new(TaskStatus::Blocked, "THIRD"));
+    }
+
+    fn write_invalid_utf8(p: &Path) {
+        let mut f = File::create(p).unwrap();
+        f.write_all(b"* FIRST\r\n* caf\xc3\xa9 \xff\xfe bytes\n+ LAST\n")
+            .unwrap();
+    }
+
+    #[test]
+    fn test_load_strict_invalid_utf8() {
+        let dir = tempdir().unwrap();
+        let p = dir.path().join("testlog");
+        write_invalid_utf8(&p);
+
+        match LogFile::load_strict(&p) {
+            Err(Error::Parse(err)) => {
+                assert_eq!(err.path(), p.as_path());
+                assert_eq!(err.line(), 2);
+                assert_eq!(err.column(), 8);
+            }
+            _ => panic!("Expected parse error"),
+        }
+
+        let err = LogFile::load(&p).err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(err.to_string().ends_with(":2:8: invalid UTF-8"));
+    }
+
+    #[test]
+    fn test_load_lossy_invalid_utf8() {
+        let dir = tempdir().unwrap();
+        let p = dir.path().join("testlog");
+        write_invalid_utf8(&p);
+
+        let lf = LogFile::load_with_mode(&p, ParseMode::Lossy).unwrap();
+        let expected = [
+            Task::new(TaskStatus::ToDo, "FIRST"),
+            Task::new(TaskStatus::ToDo, "caf\u{e9} \u{fffd}\u{fffd} bytes"),
+            Task::new(TaskStatus::Done, "LAST"),
+        ];
+        assert_eq!(lf.tasks(), &expected[..]);
+    }
 }
@@ -0,0 +1,309 @@
+//! Keep a devlog repository under version control with git.
+//!
+//! These functions shell out to the `git` program, so git must be installed.
+//! A devlog repository is a git repository only if it has its own `.git` directory;
+//! a devlog repository nested inside some other git repository is ignored.
+
+use crate::config::Config;
+use crate::error::Error;
+use crate::hook::HOOK_LOG_NAME;
+use crate::lock::LOCK_FILE_NAME;
+use std::ffi::OsStr;
+use std::fs::{read_to_string, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+use std::process::{Command, Output};
+
+const GIT_DIR_NAME: &str = ".git";
+const GITIGNORE_NAME: &str = ".gitignore";
+const REMOTE_NAME: &str = "origin";
+
+/// Checks whether the devlog repository directory is a git repository.
+pub fn is_repo(repo_dir: &Path) -> bool {
+    repo_dir.join(GIT_DIR_NAME).exists()
+}
+
+/// Initializes a git repository in the devlog repository directory,
+/// then commits any existing files.  It is safe to call this on a directory
+/// that is already a git repository.
+pub fn init(repo_dir: &Path) -> Result<(), Error> {
+    if !is_repo(repo_dir) {
+        git(repo_dir, &["init", "--quiet"])?;
+    }
+    for name in [LOCK_FILE_NAME, HOOK_LOG_NAME] {
+        if repo_dir.join(name).exists() {
+            ignore(repo_dir, name)?;
+        }
+    }
+    commit_all(repo_dir, "Initialize devlog repository")?;
+    Ok(())
+}
+
+/// Stages and commits all changes in the devlog repository with the specified message.
+/// Returns false without committing if there are no changes.
+pub fn commit_all(repo_dir: &Path, msg: &str) -> Result<bool, Error> {
+    git(repo_dir, &["add", "--all"])?;
+    let staged = git_status(repo_dir, &["diff", "--cached", "--quiet"])?;
+    if staged.status.success() {
+        return Ok(false);
+    }
+    git(repo_dir, &["commit", "--quiet", "--message", msg])?;
+    Ok(true)
+}
+
+/// Commits all changes in the devlog repository if it is a git repository
+/// and auto-commit is enabled in the configuration.  Otherwise, this is a no-op.
+/// The changes have already been written when this is called, so a failed commit
+/// (for example, because git has no author identity) is printed as a warning instead of
+/// failing the operation.
+pub fn autocommit<W: Write>(w: &mut W, config: &Config, msg: &str) -> Result<(), Error> {
+    let repo_dir = config.repo_dir();
+    if config.git_autocommit() && is_repo(repo_dir) {
+        if let Err(err) = commit_all(repo_dir, msg) {
+            writeln!(w, "auto-commit failed: {}", err)?;
+        }
+    }
+    Ok(())
+}
+
+/// Adds the file name to the `.gitignore` file of the devlog repository, creating it if necessary,
+/// so files that devlog writes for itself (such as logs) are never committed.
+/// This works whether or not the devlog repository is a git repository yet.
+pub fn ignore(repo_dir: &Path, file_name: &str) -> Result<(), Error> {
+    let path = repo_dir.join(GITIGNORE_NAME);
+    let existing = match read_to_string(&path) {
+        Ok(s) => s,
+        Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(Error::at_path("read", &path)(err)),
+    };
+    let entry = format!("/{}", file_name);
+    if existing.lines().any(|l| l.trim() == entry) {
+        return Ok(());
+    }
+
+    let mut f = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(Error::at_path("open", &path))?;
+    if !existing.is_empty() && !existing.ends_with('\n') {
+        writeln!(f)?;
+    }
+    writeln!(f, "{}", entry)?;
+    Ok(())
+}
+
+/// Synchronizes the devlog repository with its remote:
+/// commits any pending changes, rebases onto the remote branch, and pushes.
+/// If the current branch has no upstream, it is pushed to "origin" and set as the upstream.
+pub fn sync(repo_dir: &Path) -> Result<(), Error> {
+    commit_all(repo_dir, "Sync devlog")?;
+    let upstream = git_status(
+        repo_dir,
+        &[
+            "rev-parse",
+            "--abbrev-ref",
+            "--symbolic-full-name",
+            "@{upstream}",
+        ],
+    )?;
+    if upstream.status.success() {
+        git(repo_dir, &["pull", "--rebase", "--quiet"])?;
+        git(repo_dir, &["push", "--quiet"])?;
+    } else {
+        git(
+            repo_dir,
+            &["push", "--quiet", "--set-upstream", REMOTE_NAME, "HEAD"],
+        )?;
+    }
+    Ok(())
+}
+
+// Runs git and fails if it exits with an unsuccessful status.
+fn git<S: AsRef<OsStr>>(repo_dir: &Path, args: &[S]) -> Result<Output, Error> {
+    let output = git_status(repo_dir, args)?;
+    if output.status.success() {
+        Ok(output)
+    } else {
+        let cmd: Vec<String> = args
+            .iter()
+            .map(|a| a.as_ref().to_string_lossy().to_string())
+            .collect();
+        Err(Error::GitFailed(format!(
+            "git {}: {}",
+            cmd.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+// Runs git and returns its output, whether or not it succeeded.
+fn git_status<S: AsRef<OsStr>>(repo_dir: &Path, args: &[S]) -> Result<Output, Error> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_dir)
+        .args(args)
+        .output()?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::LogRepository;
+    use std::fs::{read_to_string, set_permissions, File, OpenOptions, Permissions};
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::tempdir;
+
+    fn configure_user(dir: &Path) {
+        git(dir, &["config", "user.name", "Test"]).unwrap();
+        git(dir, &["config", "user.email", "test@example.com"]).unwrap();
+        git(dir, &["config", "commit.gpgsign", "false"]).unwrap();
+    }
+
+    fn init_repo(dir: &Path) {
+        let repo = LogRepository::new(dir);
+        repo.init().unwrap();
+        git(dir, &["init", "--quiet"]).unwrap();
+        configure_user(dir);
+        init(dir).unwrap();
+    }
+
+    fn append(p: &Path, s: &str) {
+        let mut f = OpenOptions::new().append(true).open(p).unwrap();
+        write!(f, "{}", s).unwrap();
+    }
+
+    fn log_messages(dir: &Path) -> Vec<String> {
+        let output = git(dir, &["log", "--format=%s"]).unwrap();
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_init() {
+        let dir = tempdir().unwrap();
+        assert!(!is_repo(dir.path()));
+        init_repo(dir.path());
+        assert!(is_repo(dir.path()));
+        assert_eq!(
+            log_messages(dir.path()),
+            vec!["Initialize devlog repository"]
+        );
+
+        // Initializing again is a no-op.
+        init(dir.path()).unwrap();
+        assert_eq!(log_messages(dir.path()).len(), 1);
+    }
+
+    #[test]
+    fn test_autocommit() {
+        let dir = tempdir().unwrap();
+        init_repo(dir.path());
+        let config = Config::new(dir.path(), "");
+
+        assert!(config.git_autocommit());
+        let mut out = Vec::new();
+        autocommit(&mut out, &config, "No changes").unwrap();
+        assert_eq!(log_messages(dir.path()).len(), 1);
+
+        append(&dir.path().join("000000001.devlog"), "* New task\n");
+        autocommit(&mut out, &config, "Edit 000000001.devlog").unwrap();
+        assert_eq!(log_messages(dir.path())[0], "Edit 000000001.devlog");
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_autocommit_failure_is_warning() {
+        let dir = tempdir().unwrap();
+        init_repo(dir.path());
+        let hook_path = dir.path().join(".git").join("hooks").join("pre-commit");
+        let mut f = File::create(&hook_path).unwrap();
+        writeln!(f, "#!/usr/bin/env sh\nexit 1").unwrap();
+        drop(f);
+        set_permissions(&hook_path, Permissions::from_mode(0o555)).unwrap();
+
+        append(&dir.path().join("000000001.devlog"), "* New task\n");
+        let config = Config::new(dir.path(), "");
+        let mut out = Vec::new();
+        autocommit(&mut out, &config, "Edit 000000001.devlog").unwrap();
+        assert_eq!(log_messages(dir.path()).len(), 1);
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .starts_with("auto-commit failed: "));
+    }
+
+    #[test]
+    fn test_autocommit_not_a_repo() {
+        let dir = tempdir().unwrap();
+        let config = Config::new(dir.path(), "");
+        let mut out = Vec::new();
+        autocommit(&mut out, &config, "Edit").unwrap();
+        assert!(!is_repo(dir.path()));
+    }
+
+    #[test]
+    fn test_sync_with_bare_remote() {
+        let remote = tempdir().unwrap();
+        git(remote.path(), &["init", "--quiet", "--bare"]).unwrap();
+
+        let local = tempdir().unwrap();
+        init_repo(local.path());
+        git(
+            local.path(),
+            &[
+                OsStr::new("remote"),
+                OsStr::new("add"),
+                OsStr::new(REMOTE_NAME),
+                remote.path().as_os_str(),
+            ],
+        )
+        .unwrap();
+
+        // First sync pushes and sets the upstream branch.
+        sync(local.path()).unwrap();
+
+        // Another clone pushes a change...
+        let other_parent = tempdir().unwrap();
+        let other = other_parent.path().join("clone");
+        git(
+            other_parent.path(),
+            &[
+                OsStr::new("clone"),
+                OsStr::new("--quiet"),
+                remote.path().as_os_str(),
+                other.as_os_str(),
+            ],
+        )
+        .unwrap();
+        configure_user(&other);
+        append(&other.join("000000001.devlog"), "* From other\n");
+        sync(&other).unwrap();
+
+        // ...while the first has a local change in another file.
+        let mut f = File::create(local.path().join("000000002.devlog")).unwrap();
+        writeln!(f, "* From local").unwrap();
+        sync(local.path()).unwrap();
+
+        let s = read_to_string(local.path().join("000000001.devlog")).unwrap();
+        assert!(s.ends_with("* From other\n"));
+
+        sync(&other).unwrap();
+        let s = read_to_string(other.join("000000002.devlog")).unwrap();
+        assert_eq!(s, "* From local\n");
+    }
+
+    #[test]
+    fn test_ignore() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(GITIGNORE_NAME);
+        File::create(&path).unwrap().write_all(b"*.swp").unwrap();
+
+        ignore(dir.path(), "hooks.log").unwrap();
+        ignore(dir.path(), "hooks.log").unwrap();
+        assert_eq!(read_to_string(&path).unwrap(), "*.swp\n/hooks.log\n");
+    }
+}
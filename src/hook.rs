@@ -1,19 +1,52 @@
 //! A hook is an executable program called while executing a devlog command.
 //! It allows users to customize devlog for their workflows.
 //! Hooks are located in the `hooks` subdirectory of the devlog repository.
+//! Besides the single file named after the hook (e.g. `hooks/after-edit`),
+//! each executable file in the matching `.d` directory (e.g. `hooks/after-edit.d/`) runs
+//! in lexical order of its file name, after the single file.
+//!
+//! Besides its arguments, each hook receives these environment variables:
+//!
+//! * `DEVLOG_REPO`: the path to the devlog repository.
+//! * `DEVLOG_HOOK`: the name of the hook, e.g. "after-rollover".
+//! * `DEVLOG_COMMAND`: the devlog command that invoked the hook, e.g. "rollover".
+//! * `DEVLOG_SEQ_NUM`: the sequence number of the devlog entry the hook is about, if any.
+//!
+//! If `Config::hook_json` is enabled, the hook also receives a JSON object on stdin:
+//!
+//! ```json
+//! {"version": 1, "hook": "after-rollover", "command": "rollover", "seq_num": 2,
+//!  "args": ["...", "..."], "tasks": [...]}
+//! ```
+//!
+//! where `tasks` are the tasks involved in the operation, in the format described in
+//! the `json` module.  For example, after-rollover receives the tasks that were carried over.
 
 use crate::config::Config;
 use crate::error::Error;
+use crate::git;
+use crate::json::SCHEMA_VERSION;
+use chrono::Local;
+use serde_json::{json, Value};
 use std::ffi::OsStr;
-use std::fs::{create_dir_all, OpenOptions};
-use std::io::Write;
+use std::fs::{create_dir_all, read_dir, set_permissions, File, OpenOptions};
+use std::io::{stderr, Error as IOError, ErrorKind, Write};
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
 
 const HOOK_DIR_NAME: &str = "hooks";
 
+/// Name of the file in the repository that receives the output of hooks
+/// if `Config::hook_log` is enabled.
+pub const HOOK_LOG_NAME: &str = "hooks.log";
+
+const HOOK_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
 /// Defines the types of hooks a user can configure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HookType {
     /// Invoked before opening a devlog entry in a text editor.
     /// It takes a single argument: the full path to the devlog entry file.
@@ -31,9 +64,42 @@ pub enum HookType {
     /// It takes two arguments: first, the full path to the old devlog entry file;
     /// second, the full path to the new devlog entry file.
     AfterRollover,
+
+    /// Invoked after initializing a devlog repository.
+    /// It takes a single argument: the full path to the first devlog entry file.
+    AfterInit,
+
+    /// Invoked before printing the status report.
+    /// It takes a single argument: the full path to the devlog entry file being reported,
+    /// or no arguments if there is no such file.
+    BeforeStatus,
+
+    /// Invoked after printing the status report, with the same arguments as before-status.
+    AfterStatus,
+
+    /// Invoked after adding a task with `devlog add`.
+    /// It takes two arguments: first, the full path to the devlog entry file;
+    /// second, the content of the new task.
+    AfterAdd,
+
+    /// Invoked after editing, once for each task whose status changed
+    /// from to do, started, or blocked to done.
+    /// It takes two arguments: first, the full path to the devlog entry file;
+    /// second, the content of the completed task.
+    TaskCompleted,
 }
 
 impl HookType {
+    /// Returns every type of hook, in the order `devlog hooks list` shows them.
+    pub fn all() -> &'static [HookType] {
+        ALL_HOOK_TYPES
+    }
+
+    /// Returns the type of hook with the specified name, e.g. "after-edit".
+    pub fn from_name(name: &str) -> Option<HookType> {
+        ALL_HOOK_TYPES.iter().copied().find(|t| t.name() == name)
+    }
+
     /// Returns the name of the hook.
     /// This is the same as the hook's filename on disk.
     pub fn name(&self) -> String {
@@ -42,9 +108,69 @@ impl HookType {
             HookType::AfterEdit => "after-edit",
             HookType::BeforeRollover => "before-rollover",
             HookType::AfterRollover => "after-rollover",
+            HookType::AfterInit => "after-init",
+            HookType::BeforeStatus => "before-status",
+            HookType::AfterStatus => "after-status",
+            HookType::AfterAdd => "after-add",
+            HookType::TaskCompleted => "task-completed",
         }
         .to_string()
     }
+
+    /// Checks whether the hook runs before its operation,
+    /// in which case it can abort the operation by failing.
+    pub fn is_before(&self) -> bool {
+        matches!(
+            self,
+            HookType::BeforeEdit | HookType::BeforeRollover | HookType::BeforeStatus
+        )
+    }
+
+    // Status hooks run around output that other programs may read (e.g. with `--format json`),
+    // so their output is redirected to stderr.
+    fn output_to_stderr(&self) -> bool {
+        matches!(self, HookType::BeforeStatus | HookType::AfterStatus)
+    }
+}
+
+/// Describes the operation that invoked a hook.
+pub struct HookContext {
+    command: String,
+    seq_num: Option<usize>,
+    tasks: Vec<Value>,
+}
+
+impl HookContext {
+    /// Creates a context for a hook invoked by `command` (e.g. "edit")
+    /// for the devlog entry with sequence number `seq_num`.
+    pub fn new(command: &str, seq_num: Option<usize>) -> HookContext {
+        HookContext {
+            command: command.to_string(),
+            seq_num,
+            tasks: Vec::new(),
+        }
+    }
+
+    /// Sets the tasks involved in the operation, as returned by `json::task_value`.
+    pub fn with_tasks(mut self, tasks: Vec<Value>) -> HookContext {
+        self.tasks = tasks;
+        self
+    }
+
+    fn payload(&self, hook_type: &HookType, args: &[&OsStr]) -> Value {
+        let args: Vec<String> = args
+            .iter()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect();
+        json!({
+            "version": SCHEMA_VERSION,
+            "hook": hook_type.name(),
+            "command": self.command,
+            "seq_num": self.seq_num,
+            "args": args,
+            "tasks": self.tasks,
+        })
+    }
 }
 
 const ALL_HOOK_TYPES: &[HookType] = &[
@@ -52,6 +178,11 @@ const ALL_HOOK_TYPES: &[HookType] = &[
     HookType::AfterEdit,
     HookType::BeforeRollover,
     HookType::AfterRollover,
+    HookType::AfterInit,
+    HookType::BeforeStatus,
+    HookType::AfterStatus,
+    HookType::AfterAdd,
+    HookType::TaskCompleted,
 ];
 
 const HOOK_TEMPLATE: &str = "#!/usr/bin/env sh
@@ -59,11 +190,14 @@ const HOOK_TEMPLATE: &str = "#!/usr/bin/env sh
 echo \"$0 $@\"
 ";
 
-/// Creates template hook files in the specified repository.
+/// Creates template hook files in the specified repository,
+/// skipping hook files that already exist.
 /// By default, the hook files are non-executable, which means they are disabled.
-pub fn init_hooks(repo_dir: &Path) -> Result<(), Error> {
+/// Returns the types of the hooks whose files were created.
+pub fn init_hooks(repo_dir: &Path) -> Result<Vec<HookType>, Error> {
     let hook_dir = hook_dir_path(repo_dir);
     create_dir_all(&hook_dir)?;
+    let mut created = Vec::new();
     for hook_type in ALL_HOOK_TYPES {
         let mut p = hook_dir.clone();
         p.push(hook_type.name());
@@ -74,40 +208,218 @@ pub fn init_hooks(repo_dir: &Path) -> Result<(), Error> {
                 .truncate(true)
                 .write(true)
                 .open(&p)
-                .unwrap();
+                .map_err(Error::at_path("create", &p))?;
             write!(f, "{}", HOOK_TEMPLATE)?;
+            created.push(*hook_type);
         }
     }
-    Ok(())
+    Ok(created)
+}
+
+/// Describes whether the file for a hook exists and is executable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookFileState {
+    Missing,
+    Disabled,
+    Enabled,
+}
+
+/// Returns the state of the hook file, ignoring any scripts in the hook's `.d` directory.
+pub fn hook_file_state(repo_dir: &Path, hook_type: &HookType) -> Result<HookFileState, Error> {
+    let p = hook_file_path(repo_dir, hook_type);
+    if !p.exists() {
+        Ok(HookFileState::Missing)
+    } else if is_executable(&p)? {
+        Ok(HookFileState::Enabled)
+    } else {
+        Ok(HookFileState::Disabled)
+    }
+}
+
+/// Enables or disables the hook file by setting or clearing its executable bits,
+/// like `chmod +x` and `chmod -x`.  Scripts in the hook's `.d` directory are not changed.
+/// Fails if the hook file does not exist.
+pub fn set_hook_enabled(repo_dir: &Path, hook_type: &HookType, enabled: bool) -> Result<(), Error> {
+    let p = hook_file_path(repo_dir, hook_type);
+    let mut perm = p
+        .metadata()
+        .map_err(Error::at_path("change permissions of", &p))?
+        .permissions();
+    let mode = if enabled {
+        perm.mode() | 0o111
+    } else {
+        perm.mode() & !0o111
+    };
+    perm.set_mode(mode);
+    set_permissions(&p, perm).map_err(Error::at_path("change permissions of", &p))
 }
 
-/// Executes a hook command if available.
+/// Executes the hook commands if available, in the order returned by `hook_cmds`.
 /// If no hook is available (e.g. because the hook file is non-executable)
 /// then this is a no-op.
+/// The first failing command stops the remaining ones.
+/// A command that runs longer than `Config::hook_timeout` is killed and counts as failing.
+/// If a before-* hook fails and `Config::before_hook_veto` is enabled,
+/// this returns `Error::HookFailed` so the caller aborts the operation.
+/// Otherwise, a failing hook only prints a warning.
 pub fn execute_hook<W: Write>(
     w: &mut W,
     config: &Config,
     hook_type: &HookType,
     args: &[&OsStr],
+    ctx: &HookContext,
 ) -> Result<(), Error> {
-    if let Some(mut cmd) = hook_cmd(config.repo_dir(), hook_type)? {
-        let status = cmd.args(args).status()?;
-        if !status.success() {
-            if let Some(code) = status.code() {
-                writeln!(w, "{} hook exited with status {}", hook_type.name(), code)?;
+    let hook_dir = hook_dir_path(config.repo_dir());
+    for mut cmd in hook_cmds(config.repo_dir(), hook_type)? {
+        let hook_path = PathBuf::from(cmd.get_program());
+        // Name the script relative to the hooks directory, e.g. "after-edit.d/10-git".
+        let hook = hook_path
+            .strip_prefix(&hook_dir)
+            .unwrap_or(&hook_path)
+            .to_string_lossy()
+            .to_string();
+
+        cmd.args(args)
+            .env("DEVLOG_REPO", config.repo_dir())
+            .env("DEVLOG_HOOK", hook_type.name())
+            .env("DEVLOG_COMMAND", &ctx.command);
+        match ctx.seq_num {
+            Some(n) => cmd.env("DEVLOG_SEQ_NUM", n.to_string()),
+            None => cmd.env_remove("DEVLOG_SEQ_NUM"),
+        };
+        if config.hook_json() {
+            cmd.stdin(Stdio::piped());
+        }
+        if config.hook_log() {
+            let log = open_hook_log(config.repo_dir(), &hook)?;
+            cmd.stdout(log.try_clone()?).stderr(log);
+        } else if hook_type.output_to_stderr() {
+            cmd.stdout(stderr());
+        }
+
+        let mut child = cmd
+            .spawn()
+            .map_err(Error::at_path("run hook", &hook_path))?;
+
+        // The payload is written from another thread, so a hook that never reads its input
+        // cannot block devlog before the timeout is checked.
+        let writer = child.stdin.take().map(|mut stdin| {
+            let payload = ctx.payload(hook_type, args).to_string();
+            thread::spawn(move || writeln!(stdin, "{}", payload))
+        });
+        let status = wait_with_timeout(&mut child, config.hook_timeout())
+            .map_err(Error::at_path("run hook", &hook_path))?;
+        // A killed hook may leave children that still hold its input open,
+        // so the thread is only joined if the hook exited by itself.
+        if let (Some(writer), Some(_)) = (writer, status) {
+            // The hook may exit without reading its input, which is fine.
+            match writer.join().expect("Could not join hook input thread") {
+                Err(err) if err.kind() != ErrorKind::BrokenPipe => {
+                    return Err(Error::at_path("write to hook", &hook_path)(err))
+                }
+                _ => {}
             }
         }
+
+        let err = match status {
+            Some(status) if status.success() => continue,
+            Some(status) => Error::HookFailed {
+                hook,
+                code: status.code(),
+            },
+            None => Error::HookTimedOut {
+                hook,
+                timeout: config.hook_timeout().unwrap_or_default(),
+            },
+        };
+        if hook_type.is_before() && config.before_hook_veto() {
+            return Err(err);
+        }
+        writeln!(w, "{}", err)?;
+        break;
     }
     Ok(())
 }
 
+// Waits for the hook to exit, killing it if it runs longer than the timeout.
+// Returns `None` if the hook was killed.
+fn wait_with_timeout(
+    child: &mut Child,
+    timeout: Option<Duration>,
+) -> Result<Option<ExitStatus>, IOError> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return child.wait().map(Some),
+    };
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if start.elapsed() >= timeout {
+            child.kill()?;
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(HOOK_POLL_INTERVAL);
+    }
+}
+
+// Opens the hook log for appending, and writes a header line naming the hook.
+fn open_hook_log(repo_dir: &Path, hook: &str) -> Result<File, Error> {
+    let path = repo_dir.join(HOOK_LOG_NAME);
+    let mut f = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(Error::at_path("open", &path))?;
+    if git::is_repo(repo_dir) {
+        git::ignore(repo_dir, HOOK_LOG_NAME)?;
+    }
+    writeln!(f, "[{}] {}", Local::now().to_rfc3339(), hook)?;
+    Ok(f)
+}
+
+/// Retrieves all executable hook commands in the order they run:
+/// first the hook file, then the executable files in the hook's `.d` directory
+/// in lexical order of their file names.
+pub fn hook_cmds(repo_dir: &Path, hook_type: &HookType) -> Result<Vec<Command>, Error> {
+    let mut cmds: Vec<Command> = hook_cmd(repo_dir, hook_type)?.into_iter().collect();
+    cmds.extend(
+        hook_d_scripts(repo_dir, hook_type)?
+            .iter()
+            .map(Command::new),
+    );
+    Ok(cmds)
+}
+
+/// Retrieves the paths of the executable files in the hook's `.d` directory
+/// in lexical order of their file names.
+pub fn hook_d_scripts(repo_dir: &Path, hook_type: &HookType) -> Result<Vec<PathBuf>, Error> {
+    let d = hook_dir_path(repo_dir).join(format!("{}.d", hook_type.name()));
+    let mut paths = Vec::new();
+    if d.is_dir() {
+        for entry in read_dir(&d).map_err(Error::at_path("read", &d))? {
+            let p = entry.map_err(Error::at_path("read", &d))?.path();
+            if p.is_file() && is_executable(&p)? {
+                paths.push(p);
+            }
+        }
+        paths.sort();
+    }
+    Ok(paths)
+}
+
 /// Retrieves the executable hook command if it exists.
 pub fn hook_cmd(repo_dir: &Path, hook_type: &HookType) -> Result<Option<Command>, Error> {
-    let mut p = hook_dir_path(repo_dir);
-    p.push(hook_type.name());
+    let p = hook_file_path(repo_dir, hook_type);
     is_valid(&p).map(|valid| if valid { Some(Command::new(&p)) } else { None })
 }
 
+fn hook_file_path(repo_dir: &Path, hook_type: &HookType) -> PathBuf {
+    hook_dir_path(repo_dir).join(hook_type.name())
+}
+
 fn hook_dir_path(repo_dir: &Path) -> PathBuf {
     let mut p = repo_dir.to_path_buf();
     p.push(HOOK_DIR_NAME);
@@ -148,6 +460,7 @@ mod tests {
 
         let mut f = OpenOptions::new()
             .create(true)
+            .truncate(true)
             .write(true)
             .open(&p)
             .unwrap();
@@ -256,4 +569,310 @@ mod tests {
         let status = cmd.status().unwrap();
         assert!(status.success())
     }
+
+    fn create_failing_hook_file(repo_dir: &Path, hook_type: HookType) {
+        let mut p = repo_dir.to_path_buf();
+        p.push(HOOK_DIR_NAME);
+        create_dir_all(&p).unwrap();
+        p.push(hook_type.name());
+        let mut f = File::create(&p).unwrap();
+        write!(f, "#!/usr/bin/env sh\nexit 3\n").unwrap();
+        set_permissions(&p, Permissions::from_mode(0o555)).unwrap()
+    }
+
+    #[test]
+    fn test_failing_before_hook_vetoes() {
+        let repo_dir = tempdir().unwrap();
+        create_failing_hook_file(repo_dir.path(), HookType::BeforeRollover);
+        let config = Config::new(repo_dir.path(), "");
+        let mut out = Vec::new();
+        match execute_hook(&mut out, &config, &HookType::BeforeRollover, &[], &ctx()) {
+            Err(Error::HookFailed { hook, code }) => {
+                assert_eq!(hook, "before-rollover");
+                assert_eq!(code, Some(3));
+            }
+            _ => panic!("Expected hook failure"),
+        }
+    }
+
+    #[test]
+    fn test_failing_before_hook_veto_disabled() {
+        let repo_dir = tempdir().unwrap();
+        create_failing_hook_file(repo_dir.path(), HookType::BeforeEdit);
+        let mut config = Config::new(repo_dir.path(), "");
+        config.set_before_hook_veto(false);
+        let mut out = Vec::new();
+        execute_hook(&mut out, &config, &HookType::BeforeEdit, &[], &ctx()).unwrap();
+        assert_eq!(out, b"before-edit hook exited with status 3\n");
+    }
+
+    #[test]
+    fn test_failing_after_hook_warns() {
+        let repo_dir = tempdir().unwrap();
+        create_failing_hook_file(repo_dir.path(), HookType::AfterEdit);
+        let config = Config::new(repo_dir.path(), "");
+        let mut out = Vec::new();
+        execute_hook(&mut out, &config, &HookType::AfterEdit, &[], &ctx()).unwrap();
+        assert_eq!(out, b"after-edit hook exited with status 3\n");
+    }
+
+    fn ctx() -> HookContext {
+        HookContext::new("test", None)
+    }
+
+    #[test]
+    fn test_hook_context() {
+        let repo_dir = tempdir().unwrap();
+        let mut p = repo_dir.path().join(HOOK_DIR_NAME);
+        create_dir_all(&p).unwrap();
+        p.push(HookType::AfterRollover.name());
+        let mut f = File::create(&p).unwrap();
+        write!(
+            f,
+            "#!/usr/bin/env sh\nenv | grep -E '^DEVLOG_(COMMAND|HOOK|REPO|SEQ_NUM)=' | sort > \"$DEVLOG_REPO/env.out\"\ncat > \"$DEVLOG_REPO/stdin.out\"\n"
+        )
+        .unwrap();
+        drop(f);
+        set_permissions(&p, Permissions::from_mode(0o555)).unwrap();
+
+        let mut config = Config::new(repo_dir.path(), "");
+        config.set_hook_json(true);
+        let ctx = HookContext::new("rollover", Some(2)).with_tasks(vec![json!({"content": "foo"})]);
+        let mut out = Vec::new();
+        execute_hook(
+            &mut out,
+            &config,
+            &HookType::AfterRollover,
+            &[OsStr::new("a"), OsStr::new("b")],
+            &ctx,
+        )
+        .unwrap();
+
+        let env = std::fs::read_to_string(repo_dir.path().join("env.out")).unwrap();
+        let env: Vec<&str> = env.lines().collect();
+        assert_eq!(
+            env,
+            vec![
+                "DEVLOG_COMMAND=rollover".to_string(),
+                "DEVLOG_HOOK=after-rollover".to_string(),
+                format!("DEVLOG_REPO={}", repo_dir.path().display()),
+                "DEVLOG_SEQ_NUM=2".to_string(),
+            ]
+        );
+
+        let stdin = std::fs::read_to_string(repo_dir.path().join("stdin.out")).unwrap();
+        let payload: Value = serde_json::from_str(&stdin).unwrap();
+        assert_eq!(
+            payload,
+            json!({
+                "version": SCHEMA_VERSION,
+                "hook": "after-rollover",
+                "command": "rollover",
+                "seq_num": 2,
+                "args": ["a", "b"],
+                "tasks": [{"content": "foo"}],
+            })
+        );
+    }
+
+    #[test]
+    fn test_hook_ignores_unread_stdin() {
+        let repo_dir = tempdir().unwrap();
+        create_hook_dir(repo_dir.path());
+        create_hook_file(repo_dir.path(), HookType::AfterEdit, true);
+        let mut config = Config::new(repo_dir.path(), "");
+        config.set_hook_json(true);
+        let tasks = vec![json!({"content": "x".repeat(1 << 20)})];
+        let ctx = HookContext::new("edit", Some(1)).with_tasks(tasks);
+        let mut out = Vec::new();
+        execute_hook(&mut out, &config, &HookType::AfterEdit, &[], &ctx).unwrap();
+    }
+
+    fn create_hook_script(p: &Path, content: &str) {
+        create_dir_all(p.parent().unwrap()).unwrap();
+        let mut f = File::create(p).unwrap();
+        write!(f, "#!/usr/bin/env sh\n{}", content).unwrap();
+        drop(f);
+        set_permissions(p, Permissions::from_mode(0o555)).unwrap();
+    }
+
+    #[test]
+    fn test_hook_d_runs_in_lexical_order() {
+        let repo_dir = tempdir().unwrap();
+        let hook_dir = repo_dir.path().join(HOOK_DIR_NAME);
+        let d = hook_dir.join("after-edit.d");
+        let log = "echo \"$(basename \"$0\") $1\" >> \"$DEVLOG_REPO/order.out\"\n";
+        create_hook_script(&hook_dir.join("after-edit"), log);
+        create_hook_script(&d.join("20-backup"), log);
+        create_hook_script(&d.join("10-git"), log);
+
+        // Non-executable files and directories are skipped.
+        File::create(d.join("15-disabled")).unwrap();
+        create_dir_all(d.join("05-dir")).unwrap();
+
+        let config = Config::new(repo_dir.path(), "");
+        let mut out = Vec::new();
+        execute_hook(
+            &mut out,
+            &config,
+            &HookType::AfterEdit,
+            &[OsStr::new("x")],
+            &ctx(),
+        )
+        .unwrap();
+
+        let order = std::fs::read_to_string(repo_dir.path().join("order.out")).unwrap();
+        assert_eq!(order, "after-edit x\n10-git x\n20-backup x\n");
+    }
+
+    #[test]
+    fn test_hook_d_fails_fast() {
+        let repo_dir = tempdir().unwrap();
+        let d = repo_dir.path().join(HOOK_DIR_NAME).join("before-edit.d");
+        create_hook_script(&d.join("10-check"), "exit 2\n");
+        create_hook_script(&d.join("20-touch"), "touch \"$DEVLOG_REPO/ran.out\"\n");
+
+        let mut config = Config::new(repo_dir.path(), "");
+        let mut out = Vec::new();
+        match execute_hook(&mut out, &config, &HookType::BeforeEdit, &[], &ctx()) {
+            Err(Error::HookFailed { hook, code }) => {
+                assert_eq!(hook, "before-edit.d/10-check");
+                assert_eq!(code, Some(2));
+            }
+            _ => panic!("Expected hook failure"),
+        }
+        assert!(!repo_dir.path().join("ran.out").exists());
+
+        // Without the veto, the failure is a warning, but the remaining scripts still don't run.
+        config.set_before_hook_veto(false);
+        execute_hook(&mut out, &config, &HookType::BeforeEdit, &[], &ctx()).unwrap();
+        assert_eq!(out, b"before-edit.d/10-check hook exited with status 2\n");
+        assert!(!repo_dir.path().join("ran.out").exists());
+    }
+
+    #[test]
+    fn test_hook_timeout_kills_hook() {
+        let repo_dir = tempdir().unwrap();
+        let hook_dir = repo_dir.path().join(HOOK_DIR_NAME);
+        create_hook_script(&hook_dir.join("after-edit"), "exec sleep 10\n");
+
+        let mut config = Config::new(repo_dir.path(), "");
+        config.set_hook_json(true);
+        config.set_hook_timeout(Some(Duration::from_millis(100)));
+        let start = Instant::now();
+        let mut out = Vec::new();
+        execute_hook(&mut out, &config, &HookType::AfterEdit, &[], &ctx()).unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(
+            out,
+            b"after-edit hook timed out after 100ms and was killed\n"
+        );
+    }
+
+    #[test]
+    fn test_hook_timeout_vetoes_before_hook() {
+        let repo_dir = tempdir().unwrap();
+        let hook_dir = repo_dir.path().join(HOOK_DIR_NAME);
+        create_hook_script(&hook_dir.join("before-rollover"), "exec sleep 10\n");
+
+        let mut config = Config::new(repo_dir.path(), "");
+        config.set_hook_timeout(Some(Duration::from_millis(100)));
+        let mut out = Vec::new();
+        match execute_hook(&mut out, &config, &HookType::BeforeRollover, &[], &ctx()) {
+            Err(Error::HookTimedOut { hook, .. }) => assert_eq!(hook, "before-rollover"),
+            _ => panic!("Expected hook timeout"),
+        }
+    }
+
+    #[test]
+    fn test_hook_log() {
+        let repo_dir = tempdir().unwrap();
+        let hook_dir = repo_dir.path().join(HOOK_DIR_NAME);
+        create_hook_script(&hook_dir.join("after-edit"), "echo out\necho err >&2\n");
+        create_hook_script(&hook_dir.join("after-edit.d").join("10-git"), "echo git\n");
+
+        let mut config = Config::new(repo_dir.path(), "");
+        config.set_hook_log(true);
+        let mut out = Vec::new();
+        execute_hook(&mut out, &config, &HookType::AfterEdit, &[], &ctx()).unwrap();
+        assert!(out.is_empty());
+
+        let log = std::fs::read_to_string(repo_dir.path().join(HOOK_LOG_NAME)).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].ends_with("] after-edit"));
+        assert_eq!(&lines[1..3], &["out", "err"]);
+        assert!(lines[3].ends_with("] after-edit.d/10-git"));
+        assert_eq!(lines[4], "git");
+        assert!(!repo_dir.path().join(".gitignore").exists());
+    }
+
+    #[test]
+    fn test_hook_log_ignored_in_git_repo() {
+        let repo_dir = tempdir().unwrap();
+        create_dir_all(repo_dir.path().join(".git")).unwrap();
+        create_hook_script(
+            &repo_dir.path().join(HOOK_DIR_NAME).join("after-edit"),
+            "echo out\n",
+        );
+
+        let mut config = Config::new(repo_dir.path(), "");
+        config.set_hook_log(true);
+        let mut out = Vec::new();
+        execute_hook(&mut out, &config, &HookType::AfterEdit, &[], &ctx()).unwrap();
+
+        let gitignore = std::fs::read_to_string(repo_dir.path().join(".gitignore")).unwrap();
+        assert_eq!(gitignore, "/hooks.log\n");
+    }
+
+    #[test]
+    fn test_hook_type_from_name() {
+        for hook_type in HookType::all() {
+            assert_eq!(HookType::from_name(&hook_type.name()), Some(*hook_type));
+        }
+        assert_eq!(HookType::from_name("after-nothing"), None);
+    }
+
+    #[test]
+    fn test_enable_and_disable_hook() {
+        let repo_dir = tempdir().unwrap();
+        let created = init_hooks(repo_dir.path()).unwrap();
+        assert_eq!(created.len(), ALL_HOOK_TYPES.len());
+        let hook_type = HookType::AfterEdit;
+        assert_eq!(
+            hook_file_state(repo_dir.path(), &hook_type).unwrap(),
+            HookFileState::Disabled
+        );
+
+        set_hook_enabled(repo_dir.path(), &hook_type, true).unwrap();
+        assert_eq!(
+            hook_file_state(repo_dir.path(), &hook_type).unwrap(),
+            HookFileState::Enabled
+        );
+        assert!(hook_cmd(repo_dir.path(), &hook_type).unwrap().is_some());
+
+        set_hook_enabled(repo_dir.path(), &hook_type, false).unwrap();
+        assert_eq!(
+            hook_file_state(repo_dir.path(), &hook_type).unwrap(),
+            HookFileState::Disabled
+        );
+        assert!(hook_cmd(repo_dir.path(), &hook_type).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_reinit_missing_hook() {
+        let repo_dir = tempdir().unwrap();
+        init_hooks(repo_dir.path()).unwrap();
+        let p = hook_file_path(repo_dir.path(), &HookType::AfterAdd);
+        std::fs::remove_file(&p).unwrap();
+        assert_eq!(
+            hook_file_state(repo_dir.path(), &HookType::AfterAdd).unwrap(),
+            HookFileState::Missing
+        );
+        assert!(set_hook_enabled(repo_dir.path(), &HookType::AfterAdd, true).is_err());
+
+        let created = init_hooks(repo_dir.path()).unwrap();
+        assert_eq!(created, vec![HookType::AfterAdd]);
+        assert!(p.exists());
+    }
 }
@@ -0,0 +1,280 @@
+//! Export a devlog repository as a static HTML site.
+//!
+//! The site consists of an index page listing every entry by date,
+//! one page per entry, and a page listing tasks by tag.
+//! Pages include their own styles, so the site works offline without any external assets.
+//! Invalid UTF-8 in an entry is shown as U+FFFD REPLACEMENT CHARACTER, so one damaged entry
+//! does not prevent exporting the rest.
+
+use crate::error::Error;
+use crate::file::{LogFile, ParseMode};
+use crate::path::LogPath;
+use crate::repository::LogRepository;
+use crate::task::Task;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{create_dir_all, read, File};
+use std::io::Write;
+use std::path::Path;
+
+const INDEX_FILENAME: &str = "index.html";
+const TAGS_FILENAME: &str = "tags.html";
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 1em 3em; }
+nav a { margin-right: 1em; }
+.entry { font-family: monospace; white-space: pre-wrap; }
+.note { color: #666; }
+.task.todo .marker { color: #b58900; }
+.task.started .marker { color: #268bd2; }
+.task.blocked { color: #dc322f; }
+.task.done { color: #859900; text-decoration: line-through; }
+";
+
+/// Writes the HTML site for every entry in the repository to the directory `dir`,
+/// creating it if necessary.  Existing files with the same names are overwritten.
+/// Returns the number of entries exported.
+pub fn export(repo: &LogRepository, dir: &Path) -> Result<usize, Error> {
+    create_dir_all(dir)?;
+
+    let mut paths = repo.list()?;
+    paths.sort();
+
+    // Tags are sorted alphabetically, and each tag lists tasks in entry order.
+    let mut tags: BTreeMap<String, Vec<(usize, usize, Task)>> = BTreeMap::new();
+    for (i, logpath) in paths.iter().enumerate() {
+        let logfile = LogFile::load_with_mode(logpath.path(), ParseMode::Lossy)?;
+        for (n, t) in logfile.tasks_with_line_nums() {
+            for tag in t.tags() {
+                tags.entry(tag.to_string())
+                    .or_default()
+                    .push((logpath.seq_num(), n, t.clone()));
+            }
+        }
+        let prev = if i > 0 { paths.get(i - 1) } else { None };
+        let next = paths.get(i + 1);
+        let mut f = File::create(dir.join(entry_filename(logpath.seq_num())))?;
+        write_entry_page(&mut f, logpath, &logfile, prev, next)?;
+    }
+
+    let mut f = File::create(dir.join(INDEX_FILENAME))?;
+    write_index_page(&mut f, &paths)?;
+
+    let mut f = File::create(dir.join(TAGS_FILENAME))?;
+    write_tags_page(&mut f, &tags)?;
+
+    Ok(paths.len())
+}
+
+fn entry_filename(seq_num: usize) -> String {
+    format!("{:09}.html", seq_num)
+}
+
+fn write_header<W: Write>(w: &mut W, title: &str) -> Result<(), Error> {
+    write!(
+        w,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<main>\n<nav><a href=\"{}\">entries</a><a href=\"{}\">tags</a></nav>\n<h1>{}</h1>\n",
+        escape(title),
+        STYLE,
+        INDEX_FILENAME,
+        TAGS_FILENAME,
+        escape(title)
+    )?;
+    Ok(())
+}
+
+fn write_footer<W: Write>(w: &mut W) -> Result<(), Error> {
+    writeln!(w, "</main>\n</body>\n</html>")?;
+    Ok(())
+}
+
+fn write_index_page<W: Write>(w: &mut W, paths: &[LogPath]) -> Result<(), Error> {
+    write_header(w, "devlog")?;
+    writeln!(w, "<ul>")?;
+    for logpath in paths.iter().rev() {
+        writeln!(
+            w,
+            "<li><a href=\"{}\">{} &mdash; entry {}</a></li>",
+            entry_filename(logpath.seq_num()),
+            logpath.date()?,
+            logpath.seq_num()
+        )?;
+    }
+    writeln!(w, "</ul>")?;
+    write_footer(w)
+}
+
+fn write_entry_page<W: Write>(
+    w: &mut W,
+    logpath: &LogPath,
+    logfile: &LogFile,
+    prev: Option<&LogPath>,
+    next: Option<&LogPath>,
+) -> Result<(), Error> {
+    let title = format!("{} \u{2014} entry {}", logpath.date()?, logpath.seq_num());
+    write_header(w, &title)?;
+
+    write!(w, "<nav>")?;
+    if let Some(p) = prev {
+        write!(
+            w,
+            "<a href=\"{}\">previous</a>",
+            entry_filename(p.seq_num())
+        )?;
+    }
+    if let Some(p) = next {
+        write!(w, "<a href=\"{}\">next</a>", entry_filename(p.seq_num()))?;
+    }
+    writeln!(w, "</nav>")?;
+
+    // Tasks are identified by line number, so lines in code blocks are treated as notes,
+    // just like when the entry is parsed.
+    let tasks: HashMap<usize, &Task> = logfile.tasks_with_line_nums().collect();
+    let bytes = read(logpath.path()).map_err(Error::at_path("read", logpath.path()))?;
+    let content = String::from_utf8_lossy(&bytes);
+    writeln!(w, "<div class=\"entry\">")?;
+    for (i, line) in content.lines().enumerate() {
+        let n = i + 1;
+        match tasks.get(&n) {
+            Some(t) => writeln!(
+                w,
+                "<div id=\"L{}\" class=\"task {}\"><span class=\"marker\">{}</span> {}</div>",
+                n,
+                t.status().name(),
+                t.status().marker(),
+                escape(t.content())
+            )?,
+            None => writeln!(
+                w,
+                "<div id=\"L{}\" class=\"note\">{}</div>",
+                n,
+                escape(line)
+            )?,
+        }
+    }
+    writeln!(w, "</div>")?;
+    write_footer(w)
+}
+
+fn write_tags_page<W: Write>(
+    w: &mut W,
+    tags: &BTreeMap<String, Vec<(usize, usize, Task)>>,
+) -> Result<(), Error> {
+    write_header(w, "tags")?;
+    for (tag, tasks) in tags.iter() {
+        writeln!(
+            w,
+            "<h2 id=\"tag-{}\">#{}</h2>\n<ul>",
+            escape(tag),
+            escape(tag)
+        )?;
+        for (seq_num, line_num, t) in tasks.iter() {
+            writeln!(
+                w,
+                "<li class=\"task {}\"><a href=\"{}#L{}\">{}</a> {}</li>",
+                t.status().name(),
+                entry_filename(*seq_num),
+                line_num,
+                seq_num,
+                escape(&t.to_string())
+            )?;
+        }
+        writeln!(w, "</ul>")?;
+    }
+    write_footer(w)
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{read_to_string, OpenOptions};
+    use tempfile::tempdir;
+
+    fn write_entry(p: &LogPath, content: &str) {
+        let mut f = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(p.path())
+            .unwrap();
+        write!(f, "{}", content).unwrap();
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape("<a href=\"x\">Tom & Jerry's</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_export() {
+        let repo_dir = tempdir().unwrap();
+        let out_dir = tempdir().unwrap();
+        let repo = LogRepository::new(repo_dir.path());
+        let first = repo.init().unwrap();
+        let second = first.next().unwrap();
+        write_entry(&first, "+ Fix <bug> #backend\nSome notes\n");
+        write_entry(
+            &second,
+            "* Deploy #backend #release\n```\n* not a task\n```\n",
+        );
+
+        let count = export(&repo, out_dir.path()).unwrap();
+        assert_eq!(count, 2);
+
+        let index = read_to_string(out_dir.path().join(INDEX_FILENAME)).unwrap();
+        let first_link = index.find("000000001.html").unwrap();
+        let second_link = index.find("000000002.html").unwrap();
+        assert!(second_link < first_link);
+
+        let page = read_to_string(out_dir.path().join("000000001.html")).unwrap();
+        assert!(page.contains(
+            "<div id=\"L1\" class=\"task done\"><span class=\"marker\">+</span> Fix &lt;bug&gt; #backend</div>"
+        ));
+        assert!(page.contains("<div id=\"L2\" class=\"note\">Some notes</div>"));
+        assert!(page.contains("<a href=\"000000002.html\">next</a>"));
+        assert!(!page.contains("previous"));
+
+        let page = read_to_string(out_dir.path().join("000000002.html")).unwrap();
+        assert!(page.contains("<div id=\"L3\" class=\"note\">* not a task</div>"));
+
+        let tags = read_to_string(out_dir.path().join(TAGS_FILENAME)).unwrap();
+        let backend = tags.find("#backend</h2>").unwrap();
+        let release = tags.find("#release</h2>").unwrap();
+        assert!(backend < release);
+        assert!(tags.contains("<a href=\"000000001.html#L1\">1</a>"));
+        assert!(tags.contains("<a href=\"000000002.html#L1\">2</a>"));
+        assert!(!tags.contains("http"));
+    }
+
+    #[test]
+    fn test_export_invalid_utf8() {
+        let repo_dir = tempdir().unwrap();
+        let out_dir = tempdir().unwrap();
+        let repo = LogRepository::new(repo_dir.path());
+        let first = repo.init().unwrap();
+        let mut f = File::create(first.path()).unwrap();
+        f.write_all(b"* Fix \xff bug\nNotes \xfe\n").unwrap();
+
+        assert_eq!(export(&repo, out_dir.path()).unwrap(), 1);
+        let page = read_to_string(out_dir.path().join("000000001.html")).unwrap();
+        assert!(page.contains("Fix \u{fffd} bug</div>"));
+        assert!(page.contains("<div id=\"L2\" class=\"note\">Notes \u{fffd}</div>"));
+    }
+}
@@ -0,0 +1,288 @@
+//! Export dated tasks as an iCalendar (RFC 5545) file of VTODO components.
+//!
+//! Only tasks with a due date ("due:YYYY-MM-DD") or scheduled date ("scheduled:YYYY-MM-DD")
+//! are exported.  Task status maps to the STATUS property as follows:
+//!
+//! * To do: `NEEDS-ACTION`
+//! * Started: `IN-PROCESS`
+//! * Blocked: `NEEDS-ACTION`, with the category `BLOCKED`
+//! * Done: `COMPLETED`
+//!
+//! Each VTODO has a UID derived from the task content, so re-importing the file
+//! into a calendar app updates existing items instead of duplicating them.
+//! A task carried over through several entries is exported once, as it appears
+//! in the most recent entry containing it.
+
+use crate::error::Error;
+use crate::file::LogFile;
+use crate::repository::LogRepository;
+use crate::task::{Task, TaskStatus};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::io::Write;
+
+const PRODID: &str = "-//devlog//devlog//EN";
+const BLOCKED_CATEGORY: &str = "BLOCKED";
+const MAX_LINE_LEN: usize = 75;
+
+/// Returns the iCalendar STATUS value for a task status.
+pub fn status_value(s: TaskStatus) -> &'static str {
+    match s {
+        TaskStatus::ToDo | TaskStatus::Blocked => "NEEDS-ACTION",
+        TaskStatus::Started => "IN-PROCESS",
+        TaskStatus::Done => "COMPLETED",
+    }
+}
+
+/// Returns the lines of a VTODO component for the task, without line endings or folding,
+/// or `None` if the task has neither a due date nor a scheduled date.
+/// `dtstamp` is the time the iCalendar file is created.
+pub fn format_task(t: &Task, dtstamp: DateTime<Utc>) -> Option<Vec<String>> {
+    let due = t.due();
+    let scheduled = t.scheduled();
+    if due.is_none() && scheduled.is_none() {
+        return None;
+    }
+
+    let mut lines = vec![
+        "BEGIN:VTODO".to_string(),
+        format!("UID:{:016x}@devlog", uid(t)),
+        format!("DTSTAMP:{}", dtstamp.format("%Y%m%dT%H%M%SZ")),
+        format!("SUMMARY:{}", escape(&t.content_without_dates())),
+        format!("STATUS:{}", status_value(t.status())),
+    ];
+    if let Some(d) = scheduled {
+        lines.push(format!("DTSTART;VALUE=DATE:{}", d.format("%Y%m%d")));
+    }
+    if let Some(d) = due {
+        lines.push(format!("DUE;VALUE=DATE:{}", d.format("%Y%m%d")));
+    }
+
+    let mut categories: Vec<String> = t.tags().iter().map(|tag| escape(tag)).collect();
+    if t.status() == TaskStatus::Blocked {
+        categories.push(BLOCKED_CATEGORY.to_string());
+    }
+    if !categories.is_empty() {
+        lines.push(format!("CATEGORIES:{}", categories.join(",")));
+    }
+    lines.push("END:VTODO".to_string());
+    Some(lines)
+}
+
+/// Writes the dated tasks from every devlog entry in the repository as an iCalendar file.
+/// Tasks with the same UID are written once, in the order they first appear,
+/// with the status and dates from the most recent entry containing them.
+/// Returns the number of tasks exported.
+pub fn export<W: Write>(w: &mut W, repo: &LogRepository) -> Result<usize, Error> {
+    export_at(w, repo, Utc::now())
+}
+
+fn export_at<W: Write>(
+    w: &mut W,
+    repo: &LogRepository,
+    dtstamp: DateTime<Utc>,
+) -> Result<usize, Error> {
+    let tasks = load_latest_tasks(repo)?;
+    let mut count = 0;
+    write_line(w, "BEGIN:VCALENDAR")?;
+    write_line(w, "VERSION:2.0")?;
+    write_line(w, &format!("PRODID:{}", PRODID))?;
+    for lines in tasks.iter().filter_map(|t| format_task(t, dtstamp)) {
+        for line in lines.iter() {
+            write_line(w, line)?;
+        }
+        count += 1;
+    }
+    write_line(w, "END:VCALENDAR")?;
+    Ok(count)
+}
+
+// Returns the most recent occurrence of each distinct task, ordered by first occurrence.
+fn load_latest_tasks(repo: &LogRepository) -> Result<Vec<Task>, Error> {
+    let mut paths = repo.list()?;
+    paths.sort();
+    let mut tasks: Vec<Task> = Vec::new();
+    let mut index: HashMap<u64, usize> = HashMap::new();
+    for logpath in paths.iter() {
+        let f = LogFile::load_strict(logpath.path())?;
+        for t in f.tasks() {
+            match index.get(&uid(t)) {
+                Some(&i) => tasks[i] = t.clone(),
+                None => {
+                    index.insert(uid(t), tasks.len());
+                    tasks.push(t.clone());
+                }
+            }
+        }
+    }
+    Ok(tasks)
+}
+
+fn uid(t: &Task) -> u64 {
+    fnv1a(t.content_without_dates().as_bytes())
+}
+
+// Content lines end with CRLF and are folded so that no line exceeds 75 octets.
+fn write_line<W: Write>(w: &mut W, line: &str) -> Result<(), Error> {
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > MAX_LINE_LEN {
+            write!(w, "\r\n ")?;
+            len = 1;
+        }
+        write!(w, "{}", c)?;
+        len += c.len_utf8();
+    }
+    write!(w, "\r\n")?;
+    Ok(())
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// 64-bit FNV-1a, which is stable across platforms and Rust versions (unlike `DefaultHasher`).
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        hash ^= u64::from(*b);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::LogPath;
+    use chrono::{NaiveDate, TimeZone};
+    use std::fs::File;
+    use std::str;
+    use tempfile::tempdir;
+
+    fn dtstamp() -> DateTime<Utc> {
+        let d = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        Utc.from_utc_datetime(&d.and_hms_opt(3, 4, 5).unwrap())
+    }
+
+    fn write_entry(repo: &LogRepository, seq_num: usize, content: &str) {
+        let p = LogPath::new(repo.path(), seq_num);
+        let mut f = File::create(p.path()).unwrap();
+        write!(f, "{}", content).unwrap();
+    }
+
+    #[test]
+    fn test_format_task() {
+        let t = Task::new(
+            TaskStatus::Blocked,
+            "Ship release; v1, v2 #backend scheduled:2024-01-03 due:2024-01-05",
+        );
+        let lines = format_task(&t, dtstamp()).unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                "BEGIN:VTODO".to_string(),
+                format!(
+                    "UID:{:016x}@devlog",
+                    fnv1a(b"Ship release; v1, v2 #backend")
+                ),
+                "DTSTAMP:20240102T030405Z".to_string(),
+                "SUMMARY:Ship release\\; v1\\, v2 #backend".to_string(),
+                "STATUS:NEEDS-ACTION".to_string(),
+                "DTSTART;VALUE=DATE:20240103".to_string(),
+                "DUE;VALUE=DATE:20240105".to_string(),
+                "CATEGORIES:backend,BLOCKED".to_string(),
+                "END:VTODO".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_undated_task() {
+        let t = Task::new(TaskStatus::ToDo, "Ship release");
+        assert!(format_task(&t, dtstamp()).is_none());
+    }
+
+    #[test]
+    fn test_uid_is_stable_across_status_and_dates() {
+        let a = Task::new(TaskStatus::ToDo, "Ship due:2024-01-05");
+        let b = Task::new(TaskStatus::Done, "Ship due:2024-01-09");
+        assert_eq!(
+            format_task(&a, dtstamp()).unwrap()[1],
+            format_task(&b, dtstamp()).unwrap()[1]
+        );
+    }
+
+    #[test]
+    fn test_fold_long_lines() {
+        let mut buf = Vec::new();
+        let line = format!("SUMMARY:{}", "é".repeat(40));
+        write_line(&mut buf, &line).unwrap();
+        let s = str::from_utf8(&buf).unwrap();
+        assert!(s.split("\r\n").all(|l| l.len() <= MAX_LINE_LEN));
+        assert_eq!(s.replace("\r\n ", ""), format!("{}\r\n", line));
+    }
+
+    #[test]
+    fn test_export() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        write_entry(
+            &repo,
+            1,
+            "^ Review due:2024-01-05\n* Undated\n+ Deploy scheduled:2024-01-01\n",
+        );
+
+        let mut buf = Vec::new();
+        assert_eq!(export_at(&mut buf, &repo, dtstamp()).unwrap(), 2);
+        let s = str::from_utf8(&buf).unwrap();
+        assert!(s.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(s.ends_with("END:VCALENDAR\r\n"));
+        assert!(s.contains("DTSTAMP:20240102T030405Z\r\n"));
+        assert!(s.contains("SUMMARY:Review\r\nSTATUS:IN-PROCESS\r\n"));
+        assert!(s.contains("SUMMARY:Deploy\r\nSTATUS:COMPLETED\r\n"));
+        assert!(!s.contains("Undated"));
+    }
+
+    #[test]
+    fn test_export_all_entries() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        write_entry(
+            &repo,
+            1,
+            "+ Deploy scheduled:2024-01-01\n^ Review due:2024-01-05\n",
+        );
+        write_entry(
+            &repo,
+            2,
+            "+ Review due:2024-01-08\n* Renew due:2024-02-01\n",
+        );
+
+        let mut buf = Vec::new();
+        assert_eq!(export_at(&mut buf, &repo, dtstamp()).unwrap(), 3);
+        let s = str::from_utf8(&buf).unwrap();
+        let summaries: Vec<&str> = s
+            .split("\r\n")
+            .filter(|l| l.starts_with("SUMMARY:"))
+            .collect();
+        assert_eq!(
+            summaries,
+            vec!["SUMMARY:Deploy", "SUMMARY:Review", "SUMMARY:Renew"]
+        );
+
+        // The carried-over task has the status and due date from the latest entry.
+        assert!(s.contains("SUMMARY:Review\r\nSTATUS:COMPLETED\r\nDUE;VALUE=DATE:20240108\r\n"));
+        assert!(!s.contains("IN-PROCESS"));
+    }
+}
@@ -0,0 +1,125 @@
+//! Append tasks imported from other formats to the most recent devlog entry.
+//!
+//! The `markdown`, `org`, `todotxt` and `taskwarrior` modules parse their input into tasks,
+//! then call `append_to_latest`.
+
+use crate::error::Error;
+use crate::file::{append_tasks, close_code_block};
+use crate::lint::{lint, ProblemKind};
+use crate::repository::LogRepository;
+use crate::task::Task;
+
+/// Appends the tasks to the end of the most recent devlog entry file.
+///
+/// Line breaks in a task's content are replaced by spaces, so imported text cannot add
+/// lines of its own (which might start with a task marker). Tasks left without content are skipped.
+/// If the entry ends inside a code block, the block is closed first,
+/// because tasks in a code block are ignored.
+///
+/// Returns the number of tasks appended, which is zero if the repository has not been initialized.
+pub fn append_to_latest(repo: &LogRepository, tasks: &[Task]) -> Result<usize, Error> {
+    let logpath = match repo.latest()? {
+        Some(logpath) => logpath,
+        None => return Ok(0),
+    };
+    let path = logpath.path();
+    let tasks: Vec<Task> = tasks.iter().filter_map(single_line).collect();
+    if tasks.is_empty() {
+        return Ok(0);
+    }
+
+    let unterminated = lint(path)?
+        .iter()
+        .any(|p| *p.kind() == ProblemKind::UnterminatedFence);
+    if unterminated {
+        close_code_block(path).map_err(Error::at_path("append to", path))?;
+    }
+    append_tasks(path, &tasks).map_err(Error::at_path("append to", path))?;
+    Ok(tasks.len())
+}
+
+fn single_line(t: &Task) -> Option<Task> {
+    let content = t
+        .content()
+        .split(['\n', '\r'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ");
+    if content.is_empty() {
+        None
+    } else {
+        Some(Task::new(t.status(), &content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::LogFile;
+    use crate::task::TaskStatus;
+    use std::fs::{read_to_string, write};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_append_to_latest() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let logpath = repo.init().unwrap();
+        write(logpath.path(), "* Existing").unwrap();
+
+        let tasks = [
+            Task::new(TaskStatus::ToDo, "Write docs"),
+            Task::new(TaskStatus::Done, "Ship"),
+        ];
+        assert_eq!(append_to_latest(&repo, &tasks).unwrap(), 2);
+        assert_eq!(
+            read_to_string(logpath.path()).unwrap(),
+            "* Existing\n* Write docs\n+ Ship\n"
+        );
+    }
+
+    #[test]
+    fn test_append_to_uninitialized_repo() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let tasks = [Task::new(TaskStatus::ToDo, "Write docs")];
+        assert_eq!(append_to_latest(&repo, &tasks).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_line_breaks_replaced() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let logpath = repo.init().unwrap();
+        write(logpath.path(), "").unwrap();
+
+        let tasks = [
+            Task::new(TaskStatus::ToDo, "Write docs\n+ Not done\r\n"),
+            Task::new(TaskStatus::ToDo, "\n\n"),
+        ];
+        assert_eq!(append_to_latest(&repo, &tasks).unwrap(), 1);
+        let f = LogFile::load(logpath.path()).unwrap();
+        assert_eq!(
+            f.tasks(),
+            &[Task::new(TaskStatus::ToDo, "Write docs + Not done")]
+        );
+    }
+
+    #[test]
+    fn test_unterminated_code_block_closed() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let logpath = repo.init().unwrap();
+        write(logpath.path(), "* Existing\n```\nsome code").unwrap();
+
+        let tasks = [Task::new(TaskStatus::ToDo, "Write docs")];
+        assert_eq!(append_to_latest(&repo, &tasks).unwrap(), 1);
+        assert_eq!(
+            read_to_string(logpath.path()).unwrap(),
+            "* Existing\n```\nsome code\n```\n* Write docs\n"
+        );
+        let f = LogFile::load(logpath.path()).unwrap();
+        assert_eq!(f.tasks().len(), 2);
+    }
+}
@@ -0,0 +1,192 @@
+//! Machine-readable JSON output for tools built on top of devlog,
+//! such as editor plugins and dashboards.
+//!
+//! Every document is an object with a `version` field set to `SCHEMA_VERSION`.
+//! Fields may be added in later versions, but existing fields will not be
+//! removed or change meaning without incrementing the version.
+//!
+//! Each task is represented as:
+//!
+//! ```json
+//! {"status": "todo", "content": "...", "seq_num": 1, "path": "...", "line": 3}
+//! ```
+//!
+//! where `status` is one of "todo", "started", "blocked", or "done".
+
+use crate::error::Error;
+use crate::file::LogFile;
+use crate::path::LogPath;
+use crate::repository::LogRepository;
+use crate::status::DisplayMode;
+use crate::task::Task;
+use serde_json::{json, Value};
+use std::io::Write;
+
+/// Version of the JSON schema.
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// Returns the JSON representation of a task at line `line_num` of the entry at `logpath`.
+pub fn task_value(logpath: &LogPath, line_num: usize, task: &Task) -> Value {
+    json!({
+        "status": task.status().name(),
+        "content": task.content(),
+        "seq_num": logpath.seq_num(),
+        "path": logpath.path().to_string_lossy(),
+        "line": line_num,
+    })
+}
+
+/// Returns the JSON representation of a devlog entry and its tasks.
+pub fn entry_value(logpath: &LogPath, logfile: &LogFile) -> Value {
+    let tasks = logfile
+        .tasks_with_line_nums()
+        .map(|(n, t)| task_value(logpath, n, t))
+        .collect();
+    entry_with_tasks(logpath, tasks)
+}
+
+/// Prints the tasks from the "nth" most recent devlog entry as JSON.
+/// Only tasks selected by the display mode are included, in the order they appear in the file.
+/// The output has the form `{"version": 1, "entry": {...}}`, where `entry` is `null`
+/// if there is no such entry.
+pub fn print_status<W: Write>(
+    w: &mut W,
+    repo: &LogRepository,
+    num_back: usize,
+    d: DisplayMode,
+) -> Result<(), Error> {
+    let entry = match repo.nth_from_latest(num_back)? {
+        Some(logpath) => {
+            let logfile = LogFile::load_strict(logpath.path())?;
+            let tasks = logfile
+                .tasks_with_line_nums()
+                .filter(|(_, t)| d.show_status(&t.status()))
+                .map(|(n, t)| task_value(&logpath, n, t))
+                .collect();
+            entry_with_tasks(&logpath, tasks)
+        }
+        None => Value::Null,
+    };
+    write_value(w, &json!({ "version": SCHEMA_VERSION, "entry": entry }))
+}
+
+/// Prints the most recent devlog entries as JSON, starting with the most recent.
+/// `limit` is the maximum number of entries to print.
+/// The output has the form `{"version": 1, "entries": [...]}`.
+pub fn print_tail<W: Write>(w: &mut W, repo: &LogRepository, limit: usize) -> Result<(), Error> {
+    let mut entries = Vec::with_capacity(limit);
+    for logpath in repo.tail(limit)? {
+        let logfile = LogFile::load_strict(logpath.path())?;
+        entries.push(entry_value(&logpath, &logfile));
+    }
+    write_value(w, &json!({ "version": SCHEMA_VERSION, "entries": entries }))
+}
+
+fn entry_with_tasks(logpath: &LogPath, tasks: Vec<Value>) -> Value {
+    json!({
+        "seq_num": logpath.seq_num(),
+        "path": logpath.path().to_string_lossy(),
+        "tasks": tasks,
+    })
+}
+
+fn write_value<W: Write>(w: &mut W, v: &Value) -> Result<(), Error> {
+    serde_json::to_writer(&mut *w, v).map_err(std::io::Error::from)?;
+    writeln!(w)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::TaskStatus;
+    use std::fs::OpenOptions;
+    use tempfile::tempdir;
+
+    fn write_entry(p: &LogPath, lines: &[&str]) {
+        let mut f = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(p.path())
+            .unwrap();
+        for l in lines {
+            writeln!(&mut f, "{}", l).unwrap();
+        }
+    }
+
+    fn parse_output(buf: &[u8]) -> Value {
+        serde_json::from_slice(buf).unwrap()
+    }
+
+    #[test]
+    fn test_print_status() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let logpath = repo.init().unwrap();
+        write_entry(&logpath, &["Notes", "* Foo", "+ Bar"]);
+
+        let mut buf = Vec::new();
+        print_status(&mut buf, &repo, 0, DisplayMode::ShowAll).unwrap();
+        let path = logpath.path().to_string_lossy();
+        assert_eq!(
+            parse_output(&buf),
+            json!({
+                "version": 1,
+                "entry": {
+                    "seq_num": 1,
+                    "path": path,
+                    "tasks": [
+                        {"status": "todo", "content": "Foo", "seq_num": 1, "path": path, "line": 2},
+                        {"status": "done", "content": "Bar", "seq_num": 1, "path": path, "line": 3},
+                    ],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_print_status_show_only() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let logpath = repo.init().unwrap();
+        write_entry(&logpath, &["* Foo", "+ Bar"]);
+
+        let mut buf = Vec::new();
+        print_status(&mut buf, &repo, 0, DisplayMode::ShowOnly(TaskStatus::Done)).unwrap();
+        let v = parse_output(&buf);
+        assert_eq!(v["entry"]["tasks"].as_array().unwrap().len(), 1);
+        assert_eq!(v["entry"]["tasks"][0]["content"], "Bar");
+    }
+
+    #[test]
+    fn test_print_status_no_entry() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        repo.init().unwrap();
+
+        let mut buf = Vec::new();
+        print_status(&mut buf, &repo, 1, DisplayMode::ShowAll).unwrap();
+        assert_eq!(parse_output(&buf), json!({"version": 1, "entry": null}));
+    }
+
+    #[test]
+    fn test_print_tail() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let first = repo.init().unwrap();
+        let second = first.next().unwrap();
+        write_entry(&first, &["* Foo"]);
+        write_entry(&second, &["^ Bar"]);
+
+        let mut buf = Vec::new();
+        print_tail(&mut buf, &repo, 5).unwrap();
+        let v = parse_output(&buf);
+        let entries = v["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["seq_num"], 2);
+        assert_eq!(entries[0]["tasks"][0]["status"], "started");
+        assert_eq!(entries[1]["seq_num"], 1);
+        assert_eq!(entries[1]["tasks"][0]["content"], "Foo");
+    }
+}
@@ -4,21 +4,43 @@
 //! Each entry may contain tasks, which each are assigned a status.
 //!
 //! This library provides a programmatic interface to the functionality of the devlog tool.
+//!
+//! Enable the `serde` feature to derive `Serialize` and `Deserialize`
+//! for `Task`, `TaskStatus`, `LogPath`, and `LogFile`.
 
+pub mod add;
+pub mod color;
 pub mod config;
+pub mod csv;
 pub mod editor;
 pub mod error;
 pub mod file;
+pub mod git;
 pub mod hook;
+pub mod html;
+pub mod ical;
+pub mod import;
+pub mod json;
+pub mod lint;
+pub mod lock;
+pub mod markdown;
+pub mod org;
 pub mod path;
+pub mod plugin;
+pub mod report;
 pub mod repository;
 pub mod rollover;
+pub mod standup;
 pub mod status;
 pub mod task;
+pub mod taskwarrior;
+pub mod template;
+pub mod todotxt;
 
 pub use config::Config;
 pub use error::Error;
-pub use file::LogFile;
+pub use file::{LogFile, ParseMode};
 pub use path::LogPath;
+pub use plugin::DevlogPlugin;
 pub use repository::LogRepository;
 pub use task::{Task, TaskStatus};
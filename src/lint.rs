@@ -0,0 +1,208 @@
+//! Check a devlog entry file for lines that are probably mistakes.
+//!
+//! These problems do not prevent a file from loading, but they usually mean
+//! the file does not contain the tasks the user intended.
+
+use crate::error::Error;
+use crate::task::Task;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::read;
+use std::io::Write;
+use std::path::Path;
+
+const FENCE: &str = "```";
+const MARKERS: &[char] = &['*', '^', '+', '-'];
+
+/// The kinds of problem reported by the linter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProblemKind {
+    /// A line starts with a task marker that is not followed by a space, e.g. "*foo".
+    /// The line is parsed as a task, but was probably meant as something else,
+    /// or is missing the space.
+    MissingSpace(char),
+
+    /// A task marker with no content.
+    EmptyTask,
+
+    /// A code block that is opened but never closed.
+    /// Since lines in a code block are not parsed as tasks,
+    /// any tasks after the opening fence are ignored.
+    UnterminatedFence,
+
+    /// A task with the same content as an earlier task in the file.
+    /// Contains the line number of the earlier task.
+    DuplicateTask(usize),
+}
+
+/// A problem found on a line of a devlog entry file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    line_num: usize,
+    kind: ProblemKind,
+}
+
+impl Problem {
+    /// Returns the line number of the problem, starting from one.
+    pub fn line_num(&self) -> usize {
+        self.line_num
+    }
+
+    /// Returns the kind of problem.
+    pub fn kind(&self) -> &ProblemKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line_num)?;
+        match self.kind {
+            ProblemKind::MissingSpace(c) => write!(f, "missing space after task marker '{}'", c),
+            ProblemKind::EmptyTask => write!(f, "empty task"),
+            ProblemKind::UnterminatedFence => write!(
+                f,
+                "code block is never closed, so tasks after this line are ignored"
+            ),
+            ProblemKind::DuplicateTask(n) => write!(f, "duplicate of the task on line {}", n),
+        }
+    }
+}
+
+/// Checks the contents of a devlog entry file for problems.
+/// Problems are returned in order of line number.
+pub fn lint_str(s: &str) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut open_fence: Option<usize> = None;
+
+    for (i, line) in s.lines().enumerate() {
+        let line_num = i + 1;
+        if line.starts_with(FENCE) {
+            open_fence = match open_fence {
+                Some(_) => None,
+                None => Some(line_num),
+            };
+            continue;
+        } else if open_fence.is_some() {
+            continue;
+        }
+
+        let task = match Task::from_string(line) {
+            Some(t) if !is_separator(line) => t,
+            _ => continue,
+        };
+        let marker = task.status().marker();
+        let kind = if task.content().is_empty() {
+            Some(ProblemKind::EmptyTask)
+        } else if !line[1..].starts_with(char::is_whitespace) {
+            Some(ProblemKind::MissingSpace(marker))
+        } else {
+            match seen.get(task.content()) {
+                Some(&first) => Some(ProblemKind::DuplicateTask(first)),
+                None => {
+                    seen.insert(task.content().to_string(), line_num);
+                    None
+                }
+            }
+        };
+        if let Some(kind) = kind {
+            problems.push(Problem { line_num, kind });
+        }
+    }
+
+    if let Some(line_num) = open_fence {
+        problems.push(Problem {
+            line_num,
+            kind: ProblemKind::UnterminatedFence,
+        });
+        problems.sort_by_key(|p| p.line_num);
+    }
+    problems
+}
+
+// Lines like "---" or "***" are separators in Markdown, so they are not reported.
+fn is_separator(line: &str) -> bool {
+    let line = line.trim_end();
+    line.len() >= 3 && line.chars().all(|c| MARKERS.contains(&c))
+}
+
+/// Checks the devlog entry file at `path` for problems.
+pub fn lint(path: &Path) -> Result<Vec<Problem>, Error> {
+    let bytes = read(path)?;
+    Ok(lint_str(&String::from_utf8_lossy(&bytes)))
+}
+
+/// Checks the devlog entry file at `path` and prints each problem found,
+/// prefixed by the path.  Returns the number of problems.
+pub fn print<W: Write>(w: &mut W, path: &Path) -> Result<usize, Error> {
+    let problems = lint(path)?;
+    for p in problems.iter() {
+        writeln!(w, "{}: {}", path.display(), p)?;
+    }
+    Ok(problems.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(line_num: usize, kind: ProblemKind) -> Problem {
+        Problem { line_num, kind }
+    }
+
+    #[test]
+    fn test_no_problems() {
+        let s = "* foo\n^ bar\n---\n---\nNotes\n```\n*not a task\n```\n+ baz\n";
+        assert_eq!(lint_str(s), vec![]);
+    }
+
+    #[test]
+    fn test_missing_space() {
+        assert_eq!(
+            lint_str("*foo\n+bar\n"),
+            vec![
+                problem(1, ProblemKind::MissingSpace('*')),
+                problem(2, ProblemKind::MissingSpace('+')),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_task() {
+        assert_eq!(
+            lint_str("* foo\n-\n^   \n"),
+            vec![
+                problem(2, ProblemKind::EmptyTask),
+                problem(3, ProblemKind::EmptyTask),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unterminated_fence() {
+        assert_eq!(
+            lint_str("* foo\n```\ncode\n```\n```rust\n* lost\n* lost\n"),
+            vec![problem(5, ProblemKind::UnterminatedFence)]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_task() {
+        assert_eq!(
+            lint_str("* foo\n^ bar\n+ foo\n* foo\n"),
+            vec![
+                problem(3, ProblemKind::DuplicateTask(1)),
+                problem(4, ProblemKind::DuplicateTask(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            problem(3, ProblemKind::DuplicateTask(1)).to_string(),
+            "line 3: duplicate of the task on line 1"
+        );
+    }
+}
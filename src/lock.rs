@@ -0,0 +1,185 @@
+//! Advisory lock that keeps devlog processes from writing to a repository at the same time.
+//!
+//! For example, without the lock a rollover started by a cron job while the latest entry is
+//! open in the editor would copy the entry before the edit is saved, losing the edit's changes
+//! in the new entry.
+//!
+//! The lock is an operating-system file lock on `.devlog.lock` in the repository directory,
+//! so it is released automatically when the process exits, even if it crashes.
+//!
+//! A command that operates on the latest entry must find that entry after taking the lock,
+//! using `RepoLock::latest`, because a command that held the lock may have rolled over
+//! in the meantime. The lock cannot be taken twice, even by the same process, so such a
+//! command then calls the `_with_lock` variant of the operation, e.g. `rollover::rollover_with_lock`.
+
+use crate::config::Config;
+use crate::error::Error;
+use crate::git;
+use crate::path::LogPath;
+use crate::repository::LogRepository;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Name of the lock file in the repository directory.
+pub const LOCK_FILE_NAME: &str = ".devlog.lock";
+
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Holds the repository lock until it is dropped.
+pub struct RepoLock {
+    _file: File,
+    repo_dir: PathBuf,
+}
+
+impl RepoLock {
+    /// Returns the most recent devlog entry file path in the locked repository,
+    /// or `None` if the repository has not yet been initialized.
+    /// Unlike an entry found before taking the lock, this cannot be outdated by another
+    /// devlog process until the lock is dropped.
+    pub fn latest(&self) -> Result<Option<LogPath>, Error> {
+        LogRepository::new(&self.repo_dir).latest()
+    }
+}
+
+/// Takes the lock on the repository in the configuration.
+/// If another process holds the lock, this waits up to `Config::lock_timeout`,
+/// then fails with `Error::RepoLocked`.
+/// If the repository is a git repository, the lock file is added to its `.gitignore`.
+pub fn lock(config: &Config) -> Result<RepoLock, Error> {
+    let repo_dir = config.repo_dir();
+    let path = repo_dir.join(LOCK_FILE_NAME);
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .map_err(Error::at_path("open", &path))?;
+
+    let start = Instant::now();
+    loop {
+        match file.try_lock() {
+            Ok(()) => break,
+            Err(TryLockError::WouldBlock) => match config.lock_timeout() {
+                Some(timeout) if start.elapsed() < timeout => thread::sleep(LOCK_POLL_INTERVAL),
+                _ => return Err(Error::RepoLocked(repo_dir.to_path_buf())),
+            },
+            Err(TryLockError::Error(err)) => return Err(Error::at_path("lock", &path)(err)),
+        }
+    }
+
+    if git::is_repo(repo_dir) {
+        git::ignore(repo_dir, LOCK_FILE_NAME)?;
+    }
+    Ok(RepoLock {
+        _file: file,
+        repo_dir: repo_dir.to_path_buf(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::add::add_with_lock;
+    use crate::editor::open_with_lock;
+    use crate::file::{LogFile, ParseMode};
+    use crate::rollover::{rollover, rollover_with_lock};
+    use crate::task::{Task, TaskStatus};
+    use std::fs::{read_to_string, set_permissions, Permissions};
+    use std::io::Write;
+    use std::os::unix::fs::PermissionsExt;
+    use std::sync::mpsc;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_lock_held() {
+        let dir = tempdir().unwrap();
+        let config = Config::new(dir.path(), "");
+        let held = lock(&config).unwrap();
+        match lock(&config) {
+            Err(Error::RepoLocked(path)) => assert_eq!(path, dir.path()),
+            _ => panic!("Expected repository to be locked"),
+        }
+
+        drop(held);
+        lock(&config).unwrap();
+    }
+
+    #[test]
+    fn test_lock_waits_for_release() {
+        let dir = tempdir().unwrap();
+        let mut config = Config::new(dir.path(), "");
+        config.set_lock_timeout(Some(Duration::from_secs(5)));
+        let held = lock(&config).unwrap();
+        let releaser = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            drop(held);
+        });
+
+        lock(&config).unwrap();
+        releaser.join().unwrap();
+    }
+
+    #[test]
+    fn test_latest_after_waiting_for_rollover() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let first = repo.init().unwrap();
+
+        // Another process holds the lock while it rolls over to entry 2.
+        let (locked_tx, locked_rx) = mpsc::channel();
+        let repo_dir = dir.path().to_path_buf();
+        let roller = thread::spawn(move || {
+            let config = Config::new(&repo_dir, "");
+            let held = lock(&config).unwrap();
+            locked_tx.send(()).unwrap();
+            thread::sleep(Duration::from_millis(100));
+            let mut out = Vec::new();
+            rollover_with_lock(&mut out, &config, &held, &first, ParseMode::Strict).unwrap();
+        });
+        locked_rx.recv().unwrap();
+
+        let editor_path = dir.path().join("editor.sh");
+        let mut f = File::create(&editor_path).unwrap();
+        writeln!(f, "#!/usr/bin/env sh\necho '* Edited' >> \"$1\"").unwrap();
+        drop(f);
+        set_permissions(&editor_path, Permissions::from_mode(0o555)).unwrap();
+        let mut config = Config::new(dir.path(), editor_path.to_str().unwrap());
+        config.set_lock_timeout(Some(Duration::from_secs(5)));
+
+        let held = lock(&config).unwrap();
+        roller.join().unwrap();
+        let latest = held.latest().unwrap().unwrap();
+        assert_eq!(latest.seq_num(), 2);
+
+        let mut out = Vec::new();
+        open_with_lock(&mut out, &config, &held, latest.path()).unwrap();
+        let task = Task::new(TaskStatus::ToDo, "Added");
+        add_with_lock(&mut out, &config, &held, &latest, &task).unwrap();
+        let contents = read_to_string(latest.path()).unwrap();
+        assert!(contents.ends_with("* Edited\n* Added\n"));
+
+        let (next, _) =
+            rollover_with_lock(&mut out, &config, &held, &latest, ParseMode::Strict).unwrap();
+        assert_eq!(next.seq_num(), 3);
+        let tasks = LogFile::load(next.path()).unwrap();
+        assert!(tasks.tasks().contains(&task));
+
+        // The entry found before waiting for the lock is outdated.
+        drop(held);
+        assert!(matches!(
+            rollover(&mut out, &config, &latest),
+            Err(Error::EntryExists(_))
+        ));
+    }
+
+    #[test]
+    fn test_lock_not_ignored_without_git() {
+        let dir = tempdir().unwrap();
+        let config = Config::new(dir.path(), "");
+        lock(&config).unwrap();
+        assert!(dir.path().join(LOCK_FILE_NAME).exists());
+        assert!(!dir.path().join(".gitignore").exists());
+    }
+}
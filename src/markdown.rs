@@ -0,0 +1,153 @@
+//! Convert between devlog tasks and GitHub-flavored Markdown checklists.
+//!
+//! To do tasks are written as `- [ ] content` and done tasks as `- [x] content`.
+//! Markdown checklists have no way to represent started or blocked tasks,
+//! so these are written as unchecked items with the annotation `_(started)_` or `_(blocked)_`.
+
+use crate::error::Error;
+use crate::file::LogFile;
+use crate::import;
+use crate::repository::LogRepository;
+use crate::task::{Task, TaskStatus};
+use std::io::{BufRead, Write};
+
+const STARTED_ANNOTATION: &str = "_(started)_";
+const BLOCKED_ANNOTATION: &str = "_(blocked)_";
+
+/// Formats a task as a Markdown checklist item.
+pub fn format_task(t: &Task) -> String {
+    match t.status() {
+        TaskStatus::ToDo => format!("- [ ] {}", t.content()),
+        TaskStatus::Started => format!("- [ ] {} {}", t.content(), STARTED_ANNOTATION),
+        TaskStatus::Blocked => format!("- [ ] {} {}", t.content(), BLOCKED_ANNOTATION),
+        TaskStatus::Done => format!("- [x] {}", t.content()),
+    }
+}
+
+/// Parses a Markdown checklist item as a task.
+/// Items may be indented and use any list marker ("-", "*", or "+").
+/// Returns `None` if the line is not a checklist item.
+pub fn parse_line(s: &str) -> Option<Task> {
+    let s = s.trim();
+    let s = s
+        .strip_prefix("- ")
+        .or_else(|| s.strip_prefix("* "))
+        .or_else(|| s.strip_prefix("+ "))?
+        .trim_start();
+
+    let (mut status, rest) = if let Some(rest) = s.strip_prefix("[ ]") {
+        (TaskStatus::ToDo, rest)
+    } else if let Some(rest) = s.strip_prefix("[x]").or_else(|| s.strip_prefix("[X]")) {
+        (TaskStatus::Done, rest)
+    } else {
+        return None;
+    };
+
+    let mut content = rest.trim();
+    if status == TaskStatus::ToDo {
+        if let Some(c) = content.strip_suffix(STARTED_ANNOTATION) {
+            status = TaskStatus::Started;
+            content = c.trim_end();
+        } else if let Some(c) = content.strip_suffix(BLOCKED_ANNOTATION) {
+            status = TaskStatus::Blocked;
+            content = c.trim_end();
+        }
+    }
+
+    if content.is_empty() {
+        None
+    } else {
+        Some(Task::new(status, content))
+    }
+}
+
+/// Writes the tasks from the "nth" most recent devlog entry as a Markdown checklist.
+pub fn export<W: Write>(w: &mut W, repo: &LogRepository, num_back: usize) -> Result<(), Error> {
+    if let Some(logpath) = repo.nth_from_latest(num_back)? {
+        let f = LogFile::load_strict(logpath.path())?;
+        for t in f.tasks() {
+            writeln!(w, "{}", format_task(t))?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads Markdown from `r` and appends each checklist item as a task
+/// to the most recent devlog entry.  Lines that are not checklist items are ignored.
+/// Returns the number of tasks imported.
+pub fn import<R: BufRead>(r: R, repo: &LogRepository) -> Result<usize, Error> {
+    let mut tasks = Vec::new();
+    for line in r.lines() {
+        if let Some(t) = parse_line(&line?) {
+            tasks.push(t);
+        }
+    }
+    import::append_to_latest(repo, &tasks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::str;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_format() {
+        let cases = [
+            (TaskStatus::ToDo, "- [ ] Foo"),
+            (TaskStatus::Started, "- [ ] Foo _(started)_"),
+            (TaskStatus::Blocked, "- [ ] Foo _(blocked)_"),
+            (TaskStatus::Done, "- [x] Foo"),
+        ];
+        for (status, expected) in cases.iter() {
+            assert_eq!(format_task(&Task::new(*status, "Foo")), *expected);
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let cases = [
+            ("- [ ] Foo", Some(Task::new(TaskStatus::ToDo, "Foo"))),
+            ("  * [x] Foo", Some(Task::new(TaskStatus::Done, "Foo"))),
+            ("+ [X] Foo", Some(Task::new(TaskStatus::Done, "Foo"))),
+            (
+                "- [ ] Foo _(started)_",
+                Some(Task::new(TaskStatus::Started, "Foo")),
+            ),
+            (
+                "- [ ] Foo _(blocked)_",
+                Some(Task::new(TaskStatus::Blocked, "Foo")),
+            ),
+            (
+                "- [x] Foo _(blocked)_",
+                Some(Task::new(TaskStatus::Done, "Foo _(blocked)_")),
+            ),
+            ("- [ ]", None),
+            ("- Foo", None),
+            ("[ ] Foo", None),
+            ("Some text", None),
+        ];
+        for (line, expected) in cases.iter() {
+            assert_eq!(parse_line(line), *expected, "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_export_and_import() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let logpath = repo.init().unwrap();
+        File::create(logpath.path()).unwrap();
+
+        let input = "## Checklist\n\n- [x] Write code\n- [ ] Review _(started)_\n\nThanks!\n";
+        assert_eq!(import(input.as_bytes(), &repo).unwrap(), 2);
+
+        let mut buf = Vec::new();
+        export(&mut buf, &repo, 0).unwrap();
+        assert_eq!(
+            str::from_utf8(&buf).unwrap(),
+            "- [x] Write code\n- [ ] Review _(started)_\n"
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Convert between devlog entries and Emacs Org mode (<https://orgmode.org/>).
+//!
+//! Task status maps to Org TODO keywords as follows:
+//!
+//! * To do: `TODO`
+//! * Started: `STARTED`
+//! * Blocked: `WAITING`
+//! * Done: `DONE`
+//!
+//! Each devlog entry is exported as a top-level heading with the entry date as
+//! an inactive timestamp, and each task as a second-level heading beneath it.
+//! Since these keywords are not all Org defaults, the export begins with a `#+TODO:` line
+//! declaring them.
+
+use crate::error::Error;
+use crate::file::LogFile;
+use crate::import;
+use crate::repository::LogRepository;
+use crate::task::{Task, TaskStatus};
+use chrono::NaiveDate;
+use std::io::{BufRead, Write};
+
+const TODO_KEYWORDS_LINE: &str = "#+TODO: TODO STARTED WAITING | DONE";
+
+/// Returns the Org TODO keyword for a task status.
+pub fn keyword(s: TaskStatus) -> &'static str {
+    match s {
+        TaskStatus::ToDo => "TODO",
+        TaskStatus::Started => "STARTED",
+        TaskStatus::Blocked => "WAITING",
+        TaskStatus::Done => "DONE",
+    }
+}
+
+fn status_from_keyword(s: &str) -> Option<TaskStatus> {
+    match s {
+        "TODO" => Some(TaskStatus::ToDo),
+        "STARTED" => Some(TaskStatus::Started),
+        "WAITING" => Some(TaskStatus::Blocked),
+        "DONE" => Some(TaskStatus::Done),
+        _ => None,
+    }
+}
+
+/// Formats the heading for a devlog entry, e.g. `* Entry 3 [2024-01-02 Tue]`.
+pub fn format_entry_heading(seq_num: usize, date: NaiveDate) -> String {
+    format!("* Entry {} [{}]", seq_num, date.format("%Y-%m-%d %a"))
+}
+
+/// Formats a task as a second-level Org heading, e.g. `** TODO Write docs`.
+pub fn format_task(t: &Task) -> String {
+    format!("** {} {}", keyword(t.status()), t.content())
+}
+
+/// Parses an Org heading with a TODO keyword as a task.
+/// Headings at any level are accepted.  Returns `None` if the line is not a heading
+/// or the heading does not have one of the keywords listed in the module documentation.
+pub fn parse_line(s: &str) -> Option<Task> {
+    let rest = s.trim_start_matches('*');
+    if rest.len() == s.len() || !rest.starts_with(' ') {
+        return None;
+    }
+    let rest = rest.trim_start();
+    let (kw, content) = rest.split_once(' ').unwrap_or((rest, ""));
+    let status = status_from_keyword(kw)?;
+    let content = content.trim();
+    if content.is_empty() {
+        None
+    } else {
+        Some(Task::new(status, content))
+    }
+}
+
+/// Writes every devlog entry in the repository as Org headings, oldest first.
+pub fn export<W: Write>(w: &mut W, repo: &LogRepository) -> Result<(), Error> {
+    let mut paths = repo.list()?;
+    paths.sort();
+    writeln!(w, "{}", TODO_KEYWORDS_LINE)?;
+    for logpath in paths.iter() {
+        writeln!(
+            w,
+            "{}",
+            format_entry_heading(logpath.seq_num(), logpath.date()?)
+        )?;
+        let f = LogFile::load_strict(logpath.path())?;
+        for t in f.tasks() {
+            writeln!(w, "{}", format_task(t))?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads an Org file from `r` and appends each heading with a TODO keyword as a task
+/// to the most recent devlog entry.  All other lines are ignored.
+/// Returns the number of tasks imported.
+pub fn import<R: BufRead>(r: R, repo: &LogRepository) -> Result<usize, Error> {
+    let mut tasks = Vec::new();
+    for line in r.lines() {
+        if let Some(t) = parse_line(&line?) {
+            tasks.push(t);
+        }
+    }
+    import::append_to_latest(repo, &tasks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::str;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_format_entry_heading() {
+        let d = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        assert_eq!(format_entry_heading(3, d), "* Entry 3 [2024-01-02 Tue]");
+    }
+
+    #[test]
+    fn test_format_task() {
+        let cases = [
+            (TaskStatus::ToDo, "** TODO Foo"),
+            (TaskStatus::Started, "** STARTED Foo"),
+            (TaskStatus::Blocked, "** WAITING Foo"),
+            (TaskStatus::Done, "** DONE Foo"),
+        ];
+        for (status, expected) in cases.iter() {
+            assert_eq!(format_task(&Task::new(*status, "Foo")), *expected);
+        }
+    }
+
+    #[test]
+    fn test_parse_line() {
+        let cases = [
+            ("** TODO Foo", Some(Task::new(TaskStatus::ToDo, "Foo"))),
+            (
+                "* STARTED Foo bar",
+                Some(Task::new(TaskStatus::Started, "Foo bar")),
+            ),
+            (
+                "*** WAITING Foo",
+                Some(Task::new(TaskStatus::Blocked, "Foo")),
+            ),
+            ("** DONE Foo", Some(Task::new(TaskStatus::Done, "Foo"))),
+            ("** NEXT Foo", None),
+            ("** TODO", None),
+            ("* Entry 1 [2024-01-02 Tue]", None),
+            ("**TODO Foo", None),
+            ("TODO Foo", None),
+        ];
+        for (line, expected) in cases.iter() {
+            assert_eq!(parse_line(line), *expected, "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_export_and_import() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let logpath = repo.init().unwrap();
+        File::create(logpath.path()).unwrap();
+
+        let input = "#+TITLE: Work\n* Project\n** TODO Foo\n   Some notes\n** WAITING Bar\n";
+        assert_eq!(import(input.as_bytes(), &repo).unwrap(), 2);
+
+        let mut buf = Vec::new();
+        export(&mut buf, &repo).unwrap();
+        let heading = format_entry_heading(1, logpath.date().unwrap());
+        assert_eq!(
+            str::from_utf8(&buf).unwrap(),
+            format!(
+                "#+TODO: TODO STARTED WAITING | DONE\n{}\n** TODO Foo\n** WAITING Bar\n",
+                heading
+            )
+        );
+    }
+}
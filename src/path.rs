@@ -1,6 +1,9 @@
 //! Path to a devlog entry file.
 
 use crate::error::Error;
+use chrono::{DateTime, Local, NaiveDate};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::path::{Path, PathBuf};
 
@@ -11,6 +14,7 @@ pub const MAX_SEQ_NUM: usize = 999_999_999;
 pub const NUM_DIGITS: usize = 9;
 
 #[derive(Debug, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct LogPath {
     path: PathBuf,
     seq_num: usize,
@@ -69,6 +73,14 @@ impl LogPath {
     pub fn path(&self) -> &Path {
         &self.path
     }
+
+    /// Returns the date the devlog entry file was last modified, in the local timezone.
+    /// This is the date of the last work recorded in the entry, unless the file was modified
+    /// later by other means, e.g. copied, restored from a backup, or checked out by git.
+    pub fn date(&self) -> Result<NaiveDate, Error> {
+        let modified = self.path.metadata()?.modified()?;
+        Ok(DateTime::<Local>::from(modified).date_naive())
+    }
 }
 
 /// Order by sequence number.
@@ -95,6 +107,10 @@ impl PartialEq for LogPath {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
+    use std::fs::File;
+    use std::time::SystemTime;
+    use tempfile::tempdir;
 
     fn dir() -> PathBuf {
         From::from(String::from("/foo/bar"))
@@ -182,7 +198,7 @@ mod tests {
         let p = LogPath::new(&d, MAX_SEQ_NUM).next();
         match p {
             Err(Error::LogFileLimitExceeded) => {}
-            _ => assert!(false),
+            _ => panic!("Expected LogFileLimitExceeded"),
         }
     }
 
@@ -196,4 +212,29 @@ mod tests {
         assert!(p2 > p1);
         assert!(p2 == p3);
     }
+
+    #[test]
+    fn test_date() {
+        let dir = tempdir().unwrap();
+        let p = LogPath::new(dir.path(), 1);
+        let f = File::create(p.path()).unwrap();
+        let d = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let mtime: SystemTime = Local
+            .from_local_datetime(&d.and_hms_opt(12, 0, 0).unwrap())
+            .unwrap()
+            .into();
+        f.set_modified(mtime).unwrap();
+        assert_eq!(p.date().unwrap(), d);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_roundtrip() {
+        let p = LogPath::new(&dir(), 123);
+        let s = serde_json::to_string(&p).unwrap();
+        assert_eq!(s, r#"{"path":"/foo/bar/000000123.devlog","seq_num":123}"#);
+        let parsed: LogPath = serde_json::from_str(&s).unwrap();
+        assert_eq!(parsed.path(), p.path());
+        assert_eq!(parsed.seq_num(), p.seq_num());
+    }
 }
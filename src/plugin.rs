@@ -0,0 +1,291 @@
+//! Extend devlog in-process from Rust code that uses the devlog library.
+//!
+//! A plugin receives the same lifecycle events as hooks (see the `hook` module),
+//! but as method calls with typed `LogPath` and `Task` values instead of external processes.
+//! Register plugins with `Config::register_plugin`. Plugins run in registration order,
+//! after the hook for the same event.
+//!
+//! The library operations `editor::open`, `rollover::rollover` and `add::add`, and their
+//! `_with_lock` variants, notify plugins themselves. Callers that implement their own
+//! operations, like the command-line app does for init and status, notify plugins with `notify`.
+
+use crate::config::Config;
+use crate::error::Error;
+use crate::hook::HookType;
+use crate::path::LogPath;
+use crate::task::Task;
+use std::io::Write;
+
+/// Callbacks for devlog lifecycle events. Every callback does nothing by default,
+/// so a plugin only implements the events it needs.
+///
+/// If a before-* callback returns an error, the operation is aborted with that error,
+/// unless `Config::before_hook_veto` is false, as for before-* hooks.
+/// Errors from other callbacks are printed as warnings, because their operation has already happened.
+pub trait DevlogPlugin {
+    /// Returns the name of the plugin, used in warnings.
+    fn name(&self) -> &str;
+
+    /// Called before opening a devlog entry in a text editor.
+    fn before_edit(&self, _path: &LogPath) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called after editing a devlog entry, with the tasks in the edited entry.
+    fn after_edit(&self, _path: &LogPath, _tasks: &[Task]) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called before rolling over a devlog entry.
+    fn before_rollover(&self, _path: &LogPath) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called after rolling over a devlog entry, with the tasks carried over to the new entry.
+    fn after_rollover(&self, _old: &LogPath, _new: &LogPath, _tasks: &[Task]) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called after initializing a devlog repository, with its first entry.
+    fn after_init(&self, _path: &LogPath) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called before printing the status report for the entry, if there is one.
+    fn before_status(&self, _path: Option<&LogPath>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called after printing the status report for the entry, if there is one.
+    fn after_status(&self, _path: Option<&LogPath>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called after adding a task to a devlog entry.
+    fn after_add(&self, _path: &LogPath, _task: &Task) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called after editing, once for each task whose status changed
+    /// from to do, started, or blocked to done.
+    fn task_completed(&self, _path: &LogPath, _task: &Task) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Calls `callback` for each plugin registered in the configuration, in registration order.
+/// For a before-* event, the first error stops the remaining plugins and is returned,
+/// so the caller can abort the operation, unless `Config::before_hook_veto` is false.
+/// Otherwise, errors are printed as warnings.
+pub fn notify<W, F>(
+    w: &mut W,
+    config: &Config,
+    hook_type: &HookType,
+    callback: F,
+) -> Result<(), Error>
+where
+    W: Write,
+    F: Fn(&dyn DevlogPlugin) -> Result<(), Error>,
+{
+    for plugin in config.plugins() {
+        if let Err(err) = callback(plugin.as_ref()) {
+            if hook_type.is_before() && config.before_hook_veto() {
+                return Err(err);
+            }
+            writeln!(
+                w,
+                "{} plugin failed on {}: {}",
+                plugin.name(),
+                hook_type.name(),
+                err
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor;
+    use crate::repository::LogRepository;
+    use crate::rollover::rollover;
+    use crate::task::TaskStatus;
+    use std::cell::RefCell;
+    use std::fs::{set_permissions, File, Permissions};
+    use std::os::unix::fs::PermissionsExt;
+    use std::rc::Rc;
+    use tempfile::tempdir;
+
+    // Records each event it receives, and optionally vetoes rollovers.
+    struct Recorder {
+        events: Rc<RefCell<Vec<String>>>,
+        veto_rollover: bool,
+    }
+
+    impl DevlogPlugin for Recorder {
+        fn name(&self) -> &str {
+            "recorder"
+        }
+
+        fn before_edit(&self, path: &LogPath) -> Result<(), Error> {
+            self.record(format!("before-edit {}", path.seq_num()))
+        }
+
+        fn after_edit(&self, path: &LogPath, tasks: &[Task]) -> Result<(), Error> {
+            self.record(format!("after-edit {} {}", path.seq_num(), tasks.len()))
+        }
+
+        fn before_rollover(&self, path: &LogPath) -> Result<(), Error> {
+            if self.veto_rollover {
+                return Err(Error::InvalidArg("no rollover today"));
+            }
+            self.record(format!("before-rollover {}", path.seq_num()))
+        }
+
+        fn after_rollover(
+            &self,
+            old: &LogPath,
+            new: &LogPath,
+            tasks: &[Task],
+        ) -> Result<(), Error> {
+            self.record(format!(
+                "after-rollover {} {} {}",
+                old.seq_num(),
+                new.seq_num(),
+                tasks.len()
+            ))
+        }
+
+        fn task_completed(&self, _path: &LogPath, task: &Task) -> Result<(), Error> {
+            self.record(format!("task-completed {}", task.content()))
+        }
+    }
+
+    impl Recorder {
+        fn record(&self, event: String) -> Result<(), Error> {
+            self.events.borrow_mut().push(event);
+            Ok(())
+        }
+    }
+
+    struct Failing;
+
+    impl DevlogPlugin for Failing {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        fn after_edit(&self, _path: &LogPath, _tasks: &[Task]) -> Result<(), Error> {
+            Err(Error::InvalidArg("broken"))
+        }
+    }
+
+    fn recorder(config: &mut Config, veto_rollover: bool) -> Rc<RefCell<Vec<String>>> {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        config.register_plugin(Box::new(Recorder {
+            events: events.clone(),
+            veto_rollover,
+        }));
+        events
+    }
+
+    #[test]
+    fn test_edit_notifies_plugins() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let logpath = repo.init().unwrap();
+
+        let editor_path = dir.path().join("editor.sh");
+        let mut f = File::create(&editor_path).unwrap();
+        writeln!(
+            f,
+            "#!/usr/bin/env sh\nsed -i 's/^\\* Use an asterisk/+ Use an asterisk/' \"$1\""
+        )
+        .unwrap();
+        drop(f);
+        set_permissions(&editor_path, Permissions::from_mode(0o555)).unwrap();
+
+        let mut config = Config::new(dir.path(), editor_path.to_str().unwrap());
+        let events = recorder(&mut config, false);
+        config.register_plugin(Box::new(Failing));
+        let mut out = Vec::new();
+        editor::open(&mut out, &config, logpath.path()).unwrap();
+
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                "before-edit 1",
+                "after-edit 1 4",
+                "task-completed Use an asterisk (*) for each task you want to complete today.",
+            ]
+        );
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "failing plugin failed on after-edit: invalid argument: broken\n"
+        );
+    }
+
+    #[test]
+    fn test_rollover_notifies_plugins() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let logpath = repo.init().unwrap();
+        let mut config = Config::new(dir.path(), "");
+        let events = recorder(&mut config, false);
+        let mut out = Vec::new();
+        rollover(&mut out, &config, &logpath).unwrap();
+        assert_eq!(
+            *events.borrow(),
+            vec!["before-rollover 1", "after-rollover 1 2 3"]
+        );
+    }
+
+    #[test]
+    fn test_plugin_vetoes_rollover() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let logpath = repo.init().unwrap();
+        let mut config = Config::new(dir.path(), "");
+        let events = recorder(&mut config, true);
+        let mut out = Vec::new();
+        match rollover(&mut out, &config, &logpath) {
+            Err(Error::InvalidArg(msg)) => assert_eq!(msg, "no rollover today"),
+            _ => panic!("Expected plugin error"),
+        }
+        assert!(events.borrow().is_empty());
+        assert_eq!(repo.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_plugin_veto_disabled() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let logpath = repo.init().unwrap();
+        let mut config = Config::new(dir.path(), "");
+        config.set_before_hook_veto(false);
+        let events = recorder(&mut config, true);
+        let mut out = Vec::new();
+        rollover(&mut out, &config, &logpath).unwrap();
+        assert_eq!(*events.borrow(), vec!["after-rollover 1 2 3"]);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "recorder plugin failed on before-rollover: invalid argument: no rollover today\n"
+        );
+    }
+
+    #[test]
+    fn test_default_callbacks_do_nothing() {
+        let dir = tempdir().unwrap();
+        let mut config = Config::new(dir.path(), "");
+        config.register_plugin(Box::new(Failing));
+        let task = Task::new(TaskStatus::Done, "x");
+        let p = LogPath::new(dir.path(), 1);
+        let mut out = Vec::new();
+        notify(&mut out, &config, &HookType::TaskCompleted, |plugin| {
+            plugin.task_completed(&p, &task)
+        })
+        .unwrap();
+        assert!(out.is_empty());
+    }
+}
@@ -0,0 +1,303 @@
+//! Summarize the devlog entries from a period of time (for example, the past week)
+//! as a Markdown report.
+
+use crate::error::Error;
+use crate::file::LogFile;
+use crate::path::LogPath;
+use crate::repository::LogRepository;
+use crate::task::{Task, TaskStatus};
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use std::collections::HashSet;
+use std::io::Write;
+
+/// The period of time covered by a report.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Period {
+    /// The current week, starting on Monday.
+    Week,
+
+    /// The current month, starting on the first day of the month.
+    Month,
+}
+
+impl Period {
+    /// Return a human-readable name for the period.
+    pub fn display_name(&self) -> &str {
+        match self {
+            Period::Week => "Weekly",
+            Period::Month => "Monthly",
+        }
+    }
+
+    /// Returns the first day of the period containing `today`.
+    pub fn start(&self, today: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Week => today - Duration::days(today.weekday().num_days_from_monday() as i64),
+            Period::Month => today - Duration::days(today.day0() as i64),
+        }
+    }
+}
+
+/// Summary of the devlog entries modified during a period.
+#[derive(Debug)]
+pub struct Report {
+    period: Period,
+    start: NaiveDate,
+    end: NaiveDate,
+    num_entries: usize,
+    num_opened: usize,
+    completed: Vec<Task>,
+    carried: Vec<Task>,
+    blocked: Vec<Task>,
+}
+
+impl Report {
+    /// Loads the report for the period ending on `today`.
+    /// An entry belongs to the period if it was last modified on or after the start of the period.
+    pub fn load(repo: &LogRepository, period: Period, today: NaiveDate) -> Result<Report, Error> {
+        let start = period.start(today);
+        let mut paths = repo.list()?;
+        paths.sort();
+
+        // The entry immediately before the period determines which tasks were already open.
+        let mut prev_tasks: Vec<Task> = Vec::new();
+        let mut entries: Vec<LogFile> = Vec::new();
+        for p in paths.iter() {
+            if in_period(p, start, today)? {
+                entries.push(LogFile::load_strict(p.path())?);
+            } else if entries.is_empty() {
+                prev_tasks = LogFile::load_strict(p.path())?.tasks().to_vec();
+            }
+        }
+
+        let mut seen: HashSet<&str> = prev_tasks.iter().map(|t| t.content()).collect();
+        let mut num_opened = 0;
+        let mut completed: Vec<Task> = Vec::new();
+        for t in entries.iter().flat_map(|f| f.tasks()) {
+            if seen.insert(t.content()) {
+                num_opened += 1;
+            }
+            if t.status() == TaskStatus::Done && !completed.iter().any(|c| c == t) {
+                completed.push(t.clone());
+            }
+        }
+
+        let carried = if entries.len() > 1 {
+            entries[0]
+                .tasks()
+                .iter()
+                .filter(|t| {
+                    entries.iter().all(|f| {
+                        f.tasks()
+                            .iter()
+                            .any(|u| is_incomplete(u) && u.content() == t.content())
+                    })
+                })
+                .cloned()
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let blocked = match entries.last() {
+            Some(f) => f
+                .tasks()
+                .iter()
+                .filter(|t| t.status() == TaskStatus::Blocked)
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+
+        Ok(Report {
+            period,
+            start,
+            end: today,
+            num_entries: entries.len(),
+            num_opened,
+            completed,
+            carried,
+            blocked,
+        })
+    }
+
+    /// Tasks completed during the period.
+    pub fn completed(&self) -> &[Task] {
+        &self.completed
+    }
+
+    /// Incomplete tasks that appeared in every entry during the period.
+    pub fn carried(&self) -> &[Task] {
+        &self.carried
+    }
+
+    /// Tasks blocked in the most recent entry during the period.
+    pub fn blocked(&self) -> &[Task] {
+        &self.blocked
+    }
+
+    /// Number of tasks added during the period.
+    pub fn num_opened(&self) -> usize {
+        self.num_opened
+    }
+
+    /// Number of tasks completed during the period.
+    pub fn num_closed(&self) -> usize {
+        self.completed.len()
+    }
+
+    /// Renders the report as Markdown.
+    pub fn render_markdown(&self) -> String {
+        let mut s = format!(
+            "# {} Report: {} to {}\n\n",
+            self.period.display_name(),
+            self.start,
+            self.end
+        );
+        s.push_str(&format!("- Entries: {}\n", self.num_entries));
+        s.push_str(&format!("- Tasks opened: {}\n", self.num_opened()));
+        s.push_str(&format!("- Tasks closed: {}\n", self.num_closed()));
+        push_section(&mut s, "Blocked", &self.blocked, "**");
+        push_section(&mut s, "Completed", &self.completed, "");
+        push_section(&mut s, "Carried Over the Whole Period", &self.carried, "");
+        s
+    }
+}
+
+/// Prints the report for the period containing the current date using the provided writer.
+pub fn print<W: Write>(w: &mut W, repo: &LogRepository, period: Period) -> Result<(), Error> {
+    let today = Local::now().date_naive();
+    let report = Report::load(repo, period, today)?;
+    write!(w, "{}", report.render_markdown())?;
+    Ok(())
+}
+
+fn in_period(p: &LogPath, start: NaiveDate, end: NaiveDate) -> Result<bool, Error> {
+    let date = p.date()?;
+    Ok(date >= start && date <= end)
+}
+
+fn is_incomplete(t: &Task) -> bool {
+    t.status() != TaskStatus::Done
+}
+
+fn push_section(s: &mut String, title: &str, tasks: &[Task], emphasis: &str) {
+    s.push_str(&format!("\n## {}\n", title));
+    if tasks.is_empty() {
+        s.push_str("_None_\n");
+    }
+    for t in tasks {
+        s.push_str(&format!("- {}{}{}\n", emphasis, t.content(), emphasis));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::fs::{File, OpenOptions};
+    use std::path::Path;
+    use std::time::SystemTime;
+    use tempfile::tempdir;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn write_entry(p: &LogPath, d: NaiveDate, tasks: &[Task]) {
+        let mut f = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(p.path())
+            .unwrap();
+        for t in tasks {
+            writeln!(&mut f, "{}", t).unwrap();
+        }
+        let mtime: SystemTime = Local
+            .from_local_datetime(&d.and_hms_opt(12, 0, 0).unwrap())
+            .unwrap()
+            .into();
+        File::open(p.path()).unwrap().set_modified(mtime).unwrap();
+    }
+
+    fn init_repo(dir_path: &Path, entries: &[(NaiveDate, &[Task])]) -> LogRepository {
+        let repo = LogRepository::new(dir_path);
+        let mut logpath = repo.init().unwrap();
+        for (i, (d, tasks)) in entries.iter().enumerate() {
+            if i > 0 {
+                logpath = logpath.next().unwrap();
+            }
+            write_entry(&logpath, *d, tasks);
+        }
+        repo
+    }
+
+    #[test]
+    fn test_period_start() {
+        // 2024-01-10 is a Wednesday
+        assert_eq!(Period::Week.start(date(2024, 1, 10)), date(2024, 1, 8));
+        assert_eq!(Period::Week.start(date(2024, 1, 8)), date(2024, 1, 8));
+        assert_eq!(Period::Month.start(date(2024, 1, 10)), date(2024, 1, 1));
+    }
+
+    #[test]
+    fn test_report_week() {
+        let dir = tempdir().unwrap();
+        let repo = init_repo(
+            dir.path(),
+            &[
+                (
+                    date(2024, 1, 5),
+                    &[
+                        Task::new(TaskStatus::ToDo, "Old"),
+                        Task::new(TaskStatus::Done, "Before"),
+                    ],
+                ),
+                (
+                    date(2024, 1, 8),
+                    &[
+                        Task::new(TaskStatus::ToDo, "Old"),
+                        Task::new(TaskStatus::Started, "New"),
+                        Task::new(TaskStatus::Done, "Quick"),
+                    ],
+                ),
+                (
+                    date(2024, 1, 9),
+                    &[
+                        Task::new(TaskStatus::Started, "Old"),
+                        Task::new(TaskStatus::Done, "New"),
+                        Task::new(TaskStatus::Blocked, "Waiting"),
+                    ],
+                ),
+            ],
+        );
+
+        let report = Report::load(&repo, Period::Week, date(2024, 1, 10)).unwrap();
+        assert_eq!(
+            report.render_markdown(),
+            "# Weekly Report: 2024-01-08 to 2024-01-10\n\n\
+             - Entries: 2\n\
+             - Tasks opened: 3\n\
+             - Tasks closed: 2\n\
+             \n## Blocked\n- **Waiting**\n\
+             \n## Completed\n- Quick\n- New\n\
+             \n## Carried Over the Whole Period\n- Old\n"
+        );
+    }
+
+    #[test]
+    fn test_report_no_entries_in_period() {
+        let dir = tempdir().unwrap();
+        let repo = init_repo(
+            dir.path(),
+            &[(date(2023, 12, 1), &[Task::new(TaskStatus::Done, "Foo")])],
+        );
+
+        let report = Report::load(&repo, Period::Month, date(2024, 1, 10)).unwrap();
+        assert_eq!(report.num_opened(), 0);
+        assert_eq!(report.num_closed(), 0);
+        assert!(report.carried().is_empty());
+        assert!(report.blocked().is_empty());
+    }
+}
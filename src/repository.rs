@@ -54,17 +54,18 @@ impl LogRepository {
     /// Initializes the repository.
     /// This creates the directory if it does not exist,
     /// as well as the first devlog entry file with sequence number one.
-    /// Fails with an `IOError` if the first devlog entry already exists.
+    /// Fails with `Error::PathIOError` if the first devlog entry already exists.
     pub fn init(&self) -> Result<LogPath, Error> {
         // Ensure the directory exists
-        create_dir_all(&self.dir)?;
+        create_dir_all(&self.dir).map_err(Error::at_path("create directory", &self.dir))?;
 
         // Create the first logfile
         let p = LogPath::new(&self.dir, 1);
         let mut f = OpenOptions::new()
             .write(true)
             .create_new(true)
-            .open(p.path())?;
+            .open(p.path())
+            .map_err(Error::at_path("create", p.path()))?;
 
         writeln!(&mut f, "{}", HELP_MSG)?;
 
@@ -80,7 +81,7 @@ impl LogRepository {
     /// Returns all paths to devlog entry files in the repository.
     /// The paths are not necessarily ordered.
     pub fn list(&self) -> Result<Vec<LogPath>, Error> {
-        let entries = read_dir(&self.dir)?;
+        let entries = read_dir(&self.dir).map_err(Error::at_path("read directory", &self.dir))?;
         let paths: Vec<LogPath> = entries
             .filter_map(|entry_result| entry_result.ok().and_then(|e| LogPath::from_path(e.path())))
             .collect();
@@ -139,7 +140,11 @@ mod tests {
         for i in 0..count {
             let seq = i + 1;
             let p = LogPath::new(dir, seq);
-            let mut f = OpenOptions::new().write(true).create(true).open(p.path())?;
+            let mut f = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(p.path())?;
             write!(f, "+ DONE")?;
             paths.push(p);
         }
@@ -209,7 +214,7 @@ mod tests {
         let repo = LogRepository::new(dir.path());
         let latest = repo.latest().unwrap();
         match latest {
-            None => assert!(false),
+            None => panic!("Expected latest path"),
             Some(p) => assert_eq!(paths[0], p),
         }
     }
@@ -3,41 +3,105 @@
 
 use crate::config::Config;
 use crate::error::Error;
-use crate::file::LogFile;
-use crate::hook::{execute_hook, HookType};
+use crate::file::{LogFile, ParseMode};
+use crate::git;
+use crate::hook::{execute_hook, HookContext, HookType};
+use crate::json;
+use crate::lock::{self, RepoLock};
 use crate::path::LogPath;
+use crate::plugin;
 use crate::task::{Task, TaskStatus};
 use std::fs::OpenOptions;
-use std::io::Write;
+use std::io::{ErrorKind, Write};
 use std::path::Path;
 
 /// Copies incomplete tasks from the latest devlog entry file
 /// to a new devlog entry file with the next sequence number.
-/// If available, the before-rollover and after-rollover hooks are invoked.
+/// If available, the before-rollover and after-rollover hooks are invoked,
+/// and registered plugins are notified.
+/// Afterwards, changes are committed if the repository is a git repository with auto-commit enabled.
+/// Fails with `Error::Parse`, without creating the new file, if the latest file is not valid UTF-8.
+/// Fails with `Error::RepoLocked` if another devlog process is writing to the repository.
 pub fn rollover<W: Write>(
     w: &mut W,
     config: &Config,
     p: &LogPath,
+) -> Result<(LogPath, usize), Error> {
+    rollover_with_mode(w, config, p, ParseMode::Strict)
+}
+
+/// Same as `rollover`, but handles invalid UTF-8 in the latest devlog entry file
+/// as specified by `mode`.
+pub fn rollover_with_mode<W: Write>(
+    w: &mut W,
+    config: &Config,
+    p: &LogPath,
+    mode: ParseMode,
+) -> Result<(LogPath, usize), Error> {
+    let lock = lock::lock(config)?;
+    rollover_with_lock(w, config, &lock, p, mode)
+}
+
+/// Same as `rollover_with_mode`, but for a caller that already holds the repository lock.
+/// Fails with `Error::EntryExists` if the entry after `p` already exists,
+/// for example because `p` is not the latest entry.
+pub fn rollover_with_lock<W: Write>(
+    w: &mut W,
+    config: &Config,
+    _lock: &RepoLock,
+    p: &LogPath,
+    mode: ParseMode,
 ) -> Result<(LogPath, usize), Error> {
     let path = p.path();
     let next = p.next()?;
     let next_path = next.path();
 
-    execute_hook(w, config, &HookType::BeforeRollover, &[path.as_os_str()])?;
-    let tasks = load_carryover_tasks(path)?;
-    create_new_logfile(next_path, &tasks)?;
+    let ctx = HookContext::new("rollover", Some(p.seq_num()));
+    execute_hook(
+        w,
+        config,
+        &HookType::BeforeRollover,
+        &[path.as_os_str()],
+        &ctx,
+    )?;
+    plugin::notify(w, config, &HookType::BeforeRollover, |plugin| {
+        plugin.before_rollover(p)
+    })?;
+    let tasks = load_carryover_tasks(path, mode)?;
+    let line_nums = create_new_logfile(next_path, &tasks)?;
+
+    let carried = line_nums
+        .iter()
+        .zip(tasks.iter())
+        .map(|(n, t)| json::task_value(&next, *n, t))
+        .collect();
+    let ctx = HookContext::new("rollover", Some(next.seq_num())).with_tasks(carried);
     execute_hook(
         w,
         config,
         &HookType::AfterRollover,
         &[path.as_os_str(), next_path.as_os_str()],
+        &ctx,
+    )?;
+    plugin::notify(w, config, &HookType::AfterRollover, |plugin| {
+        plugin.after_rollover(p, &next, &tasks)
+    })?;
+    git::autocommit(
+        w,
+        config,
+        &format!(
+            "Rollover {} tasks from entry {} to entry {}",
+            tasks.len(),
+            p.seq_num(),
+            next.seq_num()
+        ),
     )?;
 
     Ok((next, tasks.len()))
 }
 
-fn load_carryover_tasks(path: &Path) -> Result<Vec<Task>, Error> {
-    let prev = LogFile::load(path)?;
+fn load_carryover_tasks(path: &Path, mode: ParseMode) -> Result<Vec<Task>, Error> {
+    let prev = LogFile::load_with_mode(path, mode)?;
     Ok(prev
         .tasks()
         .iter()
@@ -51,17 +115,27 @@ fn load_carryover_tasks(path: &Path) -> Result<Vec<Task>, Error> {
         .collect())
 }
 
-fn create_new_logfile(next_path: &Path, tasks: &[Task]) -> Result<(), Error> {
+// Returns the line number of each task in the new file, starting from one.
+fn create_new_logfile(next_path: &Path, tasks: &[Task]) -> Result<Vec<usize>, Error> {
     let mut f = OpenOptions::new()
         .write(true)
         .create_new(true)
-        .open(next_path)?;
+        .open(next_path)
+        .map_err(|err| match err.kind() {
+            ErrorKind::AlreadyExists => Error::EntryExists(next_path.to_path_buf()),
+            _ => Error::at_path("create", next_path)(err),
+        })?;
 
+    let mut line_nums = Vec::with_capacity(tasks.len());
+    let mut line_num = 1;
     for t in tasks {
-        writeln!(f, "{}", t)?;
+        let line = t.to_string();
+        writeln!(f, "{}", line)?;
+        line_nums.push(line_num);
+        line_num += line.matches('\n').count() + 1;
     }
 
-    Ok(())
+    Ok(line_nums)
 }
 
 #[cfg(test)]
@@ -100,4 +174,119 @@ mod tests {
         paths.sort();
         assert_eq!(paths, vec![first_logpath, new_logpath]);
     }
+
+    #[test]
+    fn test_rollover_invalid_utf8() {
+        let mut out = Vec::new();
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let config = Config::new(dir.path(), "");
+        let first_logpath = repo.init().unwrap();
+        let mut f = OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .open(first_logpath.path())
+            .unwrap();
+        f.write_all(b"* Fix \xff bug\n+ Done\n").unwrap();
+
+        // Strict mode fails without creating the new logfile.
+        match rollover(&mut out, &config, &first_logpath) {
+            Err(Error::Parse(err)) => assert_eq!(err.line(), 1),
+            _ => panic!("Expected parse error"),
+        }
+        assert_eq!(repo.list().unwrap().len(), 1);
+
+        // Lossy mode keeps the task.
+        let (new_logpath, num_imported) =
+            rollover_with_mode(&mut out, &config, &first_logpath, ParseMode::Lossy).unwrap();
+        assert_eq!(num_imported, 1);
+        let logfile = LogFile::load(new_logpath.path()).unwrap();
+        assert_eq!(
+            logfile.tasks(),
+            &[Task::new(TaskStatus::ToDo, "Fix \u{fffd} bug")]
+        );
+    }
+
+    #[test]
+    fn test_rollover_vetoed_by_before_hook() {
+        use std::fs::{create_dir_all, set_permissions, File, Permissions};
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut out = Vec::new();
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let config = Config::new(dir.path(), "");
+        let first_logpath = repo.init().unwrap();
+
+        let hook_dir = dir.path().join("hooks");
+        create_dir_all(&hook_dir).unwrap();
+        let hook_path = hook_dir.join(HookType::BeforeRollover.name());
+        let mut f = File::create(&hook_path).unwrap();
+        writeln!(f, "#!/usr/bin/env sh\nexit 1").unwrap();
+        drop(f);
+        set_permissions(&hook_path, Permissions::from_mode(0o555)).unwrap();
+
+        match rollover(&mut out, &config, &first_logpath) {
+            Err(Error::HookFailed { .. }) => {}
+            _ => panic!("Expected hook failure"),
+        }
+        assert_eq!(repo.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_rollover_outdated_entry() {
+        let mut out = Vec::new();
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let config = Config::new(dir.path(), "");
+        let first_logpath = repo.init().unwrap();
+        let (second_logpath, _) = rollover(&mut out, &config, &first_logpath).unwrap();
+
+        match rollover(&mut out, &config, &first_logpath) {
+            Err(Error::EntryExists(path)) => assert_eq!(path, second_logpath.path()),
+            _ => panic!("Expected entry to exist"),
+        }
+        assert_eq!(repo.list().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_rollover_hook_line_nums() {
+        use serde_json::Value;
+        use std::fs::{create_dir_all, read_to_string, set_permissions, File, Permissions};
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut out = Vec::new();
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let mut config = Config::new(dir.path(), "");
+        config.set_hook_json(true);
+        let first_logpath = repo.init().unwrap();
+
+        let hook_dir = dir.path().join("hooks");
+        create_dir_all(&hook_dir).unwrap();
+        let hook_path = hook_dir.join(HookType::AfterRollover.name());
+        let mut f = File::create(&hook_path).unwrap();
+        writeln!(f, "#!/usr/bin/env sh\ncat > \"$DEVLOG_REPO/payload.json\"").unwrap();
+        drop(f);
+        set_permissions(&hook_path, Permissions::from_mode(0o555)).unwrap();
+
+        let (new_logpath, _) = rollover(&mut out, &config, &first_logpath).unwrap();
+        let payload: Value =
+            serde_json::from_str(&read_to_string(dir.path().join("payload.json")).unwrap())
+                .unwrap();
+        let payload_lines: Vec<(u64, &str)> = payload["tasks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| (t["line"].as_u64().unwrap(), t["content"].as_str().unwrap()))
+            .collect();
+
+        let logfile = LogFile::load(new_logpath.path()).unwrap();
+        let file_lines: Vec<(u64, &str)> = logfile
+            .tasks_with_line_nums()
+            .map(|(n, t)| (n as u64, t.content()))
+            .collect();
+        assert_eq!(payload_lines.len(), 3);
+        assert_eq!(payload_lines, file_lines);
+    }
 }
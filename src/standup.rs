@@ -0,0 +1,342 @@
+//! Generate a "standup" report summarizing recent work:
+//! tasks completed yesterday, tasks planned for today, and blocked tasks.
+
+use crate::error::Error;
+use crate::file::LogFile;
+use crate::repository::LogRepository;
+use crate::task::{Task, TaskStatus};
+use std::io::Write;
+
+/// Placeholder in a template replaced by the tasks completed yesterday.
+pub const YESTERDAY_PLACEHOLDER: &str = "{yesterday}";
+
+/// Placeholder in a template replaced by the tasks planned for today.
+pub const TODAY_PLACEHOLDER: &str = "{today}";
+
+/// Placeholder in a template replaced by the blocked tasks.
+pub const BLOCKED_PLACEHOLDER: &str = "{blocked}";
+
+/// Controls how the standup report is formatted.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Plain text, with tasks formatted the same way as in a devlog entry file.
+    Plain,
+
+    /// Markdown, with tasks formatted as a bulleted list.
+    Markdown,
+
+    /// Slack "mrkdwn", with tasks formatted as a bulleted list.
+    Slack,
+}
+
+impl OutputFormat {
+    /// Returns the template used when the user does not provide one.
+    pub fn default_template(&self) -> &'static str {
+        match self {
+            OutputFormat::Plain => {
+                "Yesterday:\n{yesterday}\n\nToday:\n{today}\n\nBlocked:\n{blocked}\n"
+            }
+            OutputFormat::Markdown => {
+                "## Yesterday\n{yesterday}\n\n## Today\n{today}\n\n## Blocked\n{blocked}\n"
+            }
+            OutputFormat::Slack => {
+                "*Yesterday*\n{yesterday}\n\n*Today*\n{today}\n\n*Blocked*\n{blocked}\n"
+            }
+        }
+    }
+
+    fn format_task(&self, t: &Task) -> String {
+        let annotation = match t.status() {
+            TaskStatus::Started => " _(in progress)_",
+            _ => "",
+        };
+        match self {
+            OutputFormat::Plain => t.to_string(),
+            OutputFormat::Markdown => format!("- {}{}", t.content(), annotation),
+            OutputFormat::Slack => format!("• {}{}", escape_slack(t.content()), annotation),
+        }
+    }
+
+    fn empty_section(&self) -> &'static str {
+        match self {
+            OutputFormat::Plain => "(none)",
+            OutputFormat::Markdown | OutputFormat::Slack => "_None_",
+        }
+    }
+}
+
+/// Tasks included in a standup report.
+#[derive(Debug)]
+pub struct Standup {
+    yesterday: Vec<Task>,
+    today: Vec<Task>,
+    blocked: Vec<Task>,
+}
+
+impl Standup {
+    /// Loads the standup report from the repository.
+    /// Completed tasks are taken from both the previous and the most recent devlog entry,
+    /// since tasks finished yesterday may not have been rolled over yet.
+    /// To do, in progress, and blocked tasks are taken from the most recent devlog entry.
+    pub fn load(repo: &LogRepository) -> Result<Standup, Error> {
+        let mut standup = Standup {
+            yesterday: Vec::new(),
+            today: Vec::new(),
+            blocked: Vec::new(),
+        };
+
+        if let Some(logpath) = repo.nth_from_latest(1)? {
+            let f = LogFile::load_strict(logpath.path())?;
+            standup
+                .yesterday
+                .extend(filter_tasks(&f, &[TaskStatus::Done]));
+        }
+
+        if let Some(logpath) = repo.latest()? {
+            let f = LogFile::load_strict(logpath.path())?;
+            standup
+                .yesterday
+                .extend(filter_tasks(&f, &[TaskStatus::Done]));
+            standup
+                .today
+                .extend(filter_tasks(&f, &[TaskStatus::Started, TaskStatus::ToDo]));
+            standup
+                .blocked
+                .extend(filter_tasks(&f, &[TaskStatus::Blocked]));
+        }
+
+        Ok(standup)
+    }
+
+    /// Tasks the user completed since the previous standup.
+    pub fn yesterday(&self) -> &[Task] {
+        &self.yesterday
+    }
+
+    /// Tasks the user is working on or plans to work on today.
+    pub fn today(&self) -> &[Task] {
+        &self.today
+    }
+
+    /// Tasks the user cannot complete due to external circumstances.
+    pub fn blocked(&self) -> &[Task] {
+        &self.blocked
+    }
+
+    /// Renders the report by replacing each placeholder in `template`
+    /// with the corresponding tasks, formatted according to `fmt`.
+    /// Placeholders are replaced in a single pass over the template,
+    /// so placeholder text within a task's content is left as is.
+    pub fn render(&self, fmt: OutputFormat, template: &str) -> String {
+        let sections = [
+            (YESTERDAY_PLACEHOLDER, &self.yesterday),
+            (TODAY_PLACEHOLDER, &self.today),
+            (BLOCKED_PLACEHOLDER, &self.blocked),
+        ];
+        let mut out = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(i) = rest.find('{') {
+            out.push_str(&rest[..i]);
+            rest = &rest[i..];
+            match sections.iter().find(|(p, _)| rest.starts_with(p)) {
+                Some((p, tasks)) => {
+                    out.push_str(&format_tasks(fmt, tasks));
+                    rest = &rest[p.len()..];
+                }
+                None => {
+                    out.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+/// Prints the standup report using the provided writer.
+/// If `template` is `None`, the default template for the output format is used.
+pub fn print<W: Write>(
+    w: &mut W,
+    repo: &LogRepository,
+    fmt: OutputFormat,
+    template: Option<&str>,
+) -> Result<(), Error> {
+    let standup = Standup::load(repo)?;
+    let template = template.unwrap_or_else(|| fmt.default_template());
+    write!(w, "{}", standup.render(fmt, template))?;
+    Ok(())
+}
+
+fn filter_tasks<'a>(f: &'a LogFile, statuses: &'a [TaskStatus]) -> impl Iterator<Item = Task> + 'a {
+    statuses
+        .iter()
+        .flat_map(move |s| f.tasks().iter().filter(move |t| t.status() == *s).cloned())
+}
+
+fn format_tasks(fmt: OutputFormat, tasks: &[Task]) -> String {
+    if tasks.is_empty() {
+        return fmt.empty_section().to_string();
+    }
+    tasks
+        .iter()
+        .map(|t| fmt.format_task(t))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// Slack requires these control characters to be escaped
+// https://api.slack.com/reference/surfaces/formatting#escaping
+fn escape_slack(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::OpenOptions;
+    use std::path::Path;
+    use std::str;
+    use tempfile::tempdir;
+
+    fn write_tasks_to_file(p: &Path, tasks: &[Task]) {
+        let mut f = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(p)
+            .unwrap();
+
+        for t in tasks {
+            writeln!(&mut f, "{}", t).unwrap();
+        }
+    }
+
+    fn init_repo_with_entries(dir_path: &Path, entries: &[&[Task]]) -> LogRepository {
+        let repo = LogRepository::new(dir_path);
+        let mut logpath = repo.init().unwrap();
+        for (i, tasks) in entries.iter().enumerate() {
+            if i > 0 {
+                logpath = logpath.next().unwrap();
+            }
+            write_tasks_to_file(logpath.path(), tasks);
+        }
+        repo
+    }
+
+    fn check_standup(
+        repo: &LogRepository,
+        fmt: OutputFormat,
+        template: Option<&str>,
+        expected: &str,
+    ) {
+        let mut buf = Vec::new();
+        print(&mut buf, repo, fmt, template).unwrap();
+        let actual = str::from_utf8(&buf).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    fn two_entries(dir_path: &Path) -> LogRepository {
+        init_repo_with_entries(
+            dir_path,
+            &[
+                &[
+                    Task::new(TaskStatus::Done, "Foo"),
+                    Task::new(TaskStatus::ToDo, "Bar"),
+                ],
+                &[
+                    Task::new(TaskStatus::ToDo, "Bar"),
+                    Task::new(TaskStatus::Started, "Baz"),
+                    Task::new(TaskStatus::Blocked, "Qux"),
+                    Task::new(TaskStatus::Done, "Boo"),
+                ],
+            ],
+        )
+    }
+
+    #[test]
+    fn test_standup_plain() {
+        let dir = tempdir().unwrap();
+        let repo = two_entries(dir.path());
+        check_standup(
+            &repo,
+            OutputFormat::Plain,
+            None,
+            "Yesterday:\n+ Foo\n+ Boo\n\nToday:\n^ Baz\n* Bar\n\nBlocked:\n- Qux\n",
+        );
+    }
+
+    #[test]
+    fn test_standup_markdown() {
+        let dir = tempdir().unwrap();
+        let repo = two_entries(dir.path());
+        check_standup(
+            &repo,
+            OutputFormat::Markdown,
+            None,
+            "## Yesterday\n- Foo\n- Boo\n\n## Today\n- Baz _(in progress)_\n- Bar\n\n## Blocked\n- Qux\n",
+        );
+    }
+
+    #[test]
+    fn test_standup_slack() {
+        let dir = tempdir().unwrap();
+        let repo = init_repo_with_entries(
+            dir.path(),
+            &[&[
+                Task::new(TaskStatus::Done, "Fix <script> & friends"),
+                Task::new(TaskStatus::ToDo, "Bar"),
+            ]],
+        );
+        check_standup(
+            &repo,
+            OutputFormat::Slack,
+            None,
+            "*Yesterday*\n• Fix &lt;script&gt; &amp; friends\n\n*Today*\n• Bar\n\n*Blocked*\n_None_\n",
+        );
+    }
+
+    #[test]
+    fn test_standup_single_entry_no_tasks() {
+        let dir = tempdir().unwrap();
+        let repo = init_repo_with_entries(dir.path(), &[&[]]);
+        check_standup(
+            &repo,
+            OutputFormat::Plain,
+            None,
+            "Yesterday:\n(none)\n\nToday:\n(none)\n\nBlocked:\n(none)\n",
+        );
+    }
+
+    #[test]
+    fn test_standup_custom_template() {
+        let dir = tempdir().unwrap();
+        let repo = two_entries(dir.path());
+        check_standup(
+            &repo,
+            OutputFormat::Plain,
+            Some("Done: {yesterday}\nBlockers: {blocked}\n"),
+            "Done: + Foo\n+ Boo\nBlockers: - Qux\n",
+        );
+    }
+
+    #[test]
+    fn test_standup_placeholder_in_task_content() {
+        let dir = tempdir().unwrap();
+        let repo = init_repo_with_entries(
+            dir.path(),
+            &[&[
+                Task::new(TaskStatus::Done, "Document the {today} placeholder"),
+                Task::new(TaskStatus::ToDo, "Escape {blocked} in {unknown} templates"),
+            ]],
+        );
+        check_standup(
+            &repo,
+            OutputFormat::Plain,
+            Some("Done: {yesterday}\nNext: {today}\nBlockers: {blocked} {other}\n"),
+            "Done: + Document the {today} placeholder\nNext: * Escape {blocked} in {unknown} templates\nBlockers: (none) {other}\n",
+        );
+    }
+}
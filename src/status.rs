@@ -1,6 +1,7 @@
 //! Report tasks from the most recent devlog entry file,
 //! grouped by task status type.
 
+use crate::color::Painter;
 use crate::error::Error;
 use crate::file::LogFile;
 use crate::repository::LogRepository;
@@ -41,9 +42,21 @@ pub fn print<W: Write>(
     repo: &LogRepository,
     num_back: usize,
     d: DisplayMode,
+) -> Result<(), Error> {
+    print_highlighted(w, repo, num_back, d, Painter::new(false))
+}
+
+/// Prints the status report using the provided writer,
+/// highlighting section names and tasks with the painter.
+pub fn print_highlighted<W: Write>(
+    w: &mut W,
+    repo: &LogRepository,
+    num_back: usize,
+    d: DisplayMode,
+    p: Painter,
 ) -> Result<(), Error> {
     let g = load_tasks_group_by_status(repo, num_back)?;
-    print_status_report(w, &g, d)
+    print_status_report(w, &g, d, p)
 }
 
 fn load_tasks_group_by_status(
@@ -52,7 +65,7 @@ fn load_tasks_group_by_status(
 ) -> Result<GroupedTasks, Error> {
     let mut grouped = GroupedTasks::new();
     if let Some(logpath) = repo.nth_from_latest(num_back)? {
-        let f = LogFile::load(logpath.path())?;
+        let f = LogFile::load_strict(logpath.path())?;
         f.tasks().iter().for_each(|t| grouped.insert(t));
     }
     Ok(grouped)
@@ -65,7 +78,12 @@ const ALL_STATUSES: &[TaskStatus] = &[
     TaskStatus::Done,
 ];
 
-fn print_status_report<W: Write>(w: &mut W, g: &GroupedTasks, d: DisplayMode) -> Result<(), Error> {
+fn print_status_report<W: Write>(
+    w: &mut W,
+    g: &GroupedTasks,
+    d: DisplayMode,
+    p: Painter,
+) -> Result<(), Error> {
     let mut has_prev = false;
     for status in ALL_STATUSES {
         if d.show_status(status) {
@@ -74,7 +92,7 @@ fn print_status_report<W: Write>(w: &mut W, g: &GroupedTasks, d: DisplayMode) ->
                 if has_prev {
                     writeln!(w)?;
                 }
-                print_section(w, status, tasks, d)?;
+                print_section(w, status, tasks, d, p)?;
                 has_prev = true;
             }
         }
@@ -88,12 +106,14 @@ fn print_section<W: Write>(
     status: &TaskStatus,
     tasks: &[Task],
     d: DisplayMode,
+    p: Painter,
 ) -> Result<(), Error> {
     if d.show_section_names() {
-        writeln!(w, "{}:", status.display_name())?;
+        let header = format!("{}:", status.display_name());
+        writeln!(w, "{}", p.header(&header))?;
     }
     for t in tasks {
-        writeln!(w, "{}", t)?;
+        writeln!(w, "{}", p.task(t))?;
     }
     Ok(())
 }
@@ -444,4 +464,22 @@ mod tests {
         // check the latest logfile
         check_status(&repo, 0, DisplayMode::ShowAll, "To Do:\n* Bar\n");
     }
+
+    #[test]
+    fn test_status_highlighted() {
+        let dir = tempdir().unwrap();
+        let repo = init_repo_with_tasks(
+            dir.path(),
+            &[
+                Task::new(TaskStatus::ToDo, "Foo"),
+                Task::new(TaskStatus::Done, "Bar"),
+            ],
+        );
+        let mut buf = Vec::new();
+        print_highlighted(&mut buf, &repo, 0, DisplayMode::ShowAll, Painter::new(true)).unwrap();
+        assert_eq!(
+            str::from_utf8(&buf).unwrap(),
+            "\x1b[1mTo Do:\x1b[0m\n\x1b[33m* Foo\x1b[0m\n\n\x1b[1mDone:\x1b[0m\n\x1b[32m+ Bar\x1b[0m\n"
+        );
+    }
 }
@@ -1,9 +1,22 @@
 //! A task is something the user wants or needs to do.
 
+use chrono::NaiveDate;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
+const DUE_KEY: &str = "due:";
+const SCHEDULED_KEY: &str = "scheduled:";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
 /// Represents the user-assigned status of a task.
+/// With the `serde` feature, it is serialized using the same names as `TaskStatus::name`.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum TaskStatus {
     /// The user has not yet started the task.
     ToDo,
@@ -28,10 +41,32 @@ impl TaskStatus {
             TaskStatus::Done => "Done",
         }
     }
+
+    /// Return a short, machine-readable name for the task status.
+    /// These are the same names accepted by the `--show` option of `devlog status`.
+    pub fn name(&self) -> &str {
+        match self {
+            TaskStatus::ToDo => "todo",
+            TaskStatus::Started => "started",
+            TaskStatus::Blocked => "blocked",
+            TaskStatus::Done => "done",
+        }
+    }
+
+    /// Return the character that marks a task with this status in a devlog entry file.
+    pub fn marker(&self) -> char {
+        match self {
+            TaskStatus::ToDo => '*',
+            TaskStatus::Started => '^',
+            TaskStatus::Blocked => '-',
+            TaskStatus::Done => '+',
+        }
+    }
 }
 
 /// A task the user wants or needs to do.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Task {
     status: TaskStatus,
     content: String,
@@ -87,17 +122,63 @@ impl Task {
     pub fn content(&self) -> &str {
         &self.content
     }
+
+    /// Returns the tags in the task content, without the leading "#".
+    /// A tag is a word starting with "#", followed by letters, digits, "-", or "_";
+    /// for example, "#backend" or "#release-1".  Punctuation around the tag,
+    /// as in "(#backend)", is ignored.
+    pub fn tags(&self) -> Vec<&str> {
+        self.content
+            .split_whitespace()
+            .map(|word| word.trim_start_matches(|c: char| c.is_ascii_punctuation() && c != '#'))
+            .filter_map(|word| word.strip_prefix('#'))
+            .map(|tag| tag.trim_end_matches(|c: char| !is_tag_char(c)))
+            .filter(|tag| !tag.is_empty() && tag.chars().all(is_tag_char))
+            .collect()
+    }
+
+    /// Returns the date the task is due, written in the task content as "due:YYYY-MM-DD".
+    /// If the content has more than one due date, the first one is returned.
+    pub fn due(&self) -> Option<NaiveDate> {
+        self.date_attr(DUE_KEY)
+    }
+
+    /// Returns the date the task is scheduled to start,
+    /// written in the task content as "scheduled:YYYY-MM-DD".
+    /// If the content has more than one scheduled date, the first one is returned.
+    pub fn scheduled(&self) -> Option<NaiveDate> {
+        self.date_attr(SCHEDULED_KEY)
+    }
+
+    /// Returns the task content without any due or scheduled dates.
+    pub fn content_without_dates(&self) -> String {
+        self.content
+            .split_whitespace()
+            .filter(|word| !is_date_attr(word, DUE_KEY) && !is_date_attr(word, SCHEDULED_KEY))
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+
+    fn date_attr(&self, key: &str) -> Option<NaiveDate> {
+        self.content
+            .split_whitespace()
+            .filter_map(|word| word.strip_prefix(key))
+            .find_map(|d| NaiveDate::parse_from_str(d, DATE_FORMAT).ok())
+    }
+}
+
+fn is_date_attr(word: &str, key: &str) -> bool {
+    word.strip_prefix(key)
+        .is_some_and(|d| NaiveDate::parse_from_str(d, DATE_FORMAT).is_ok())
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
 }
 
 impl fmt::Display for Task {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.status {
-            TaskStatus::ToDo => write!(f, "* ")?,
-            TaskStatus::Started => write!(f, "^ ")?,
-            TaskStatus::Done => write!(f, "+ ")?,
-            TaskStatus::Blocked => write!(f, "- ")?,
-        };
-        write!(f, "{}", self.content)
+        write!(f, "{} {}", self.status.marker(), self.content)
     }
 }
 
@@ -179,4 +260,48 @@ mod tests {
         let s = format!("{}", t);
         assert_eq!(s, "- BLOCKED");
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_roundtrip() {
+        let t = Task::new(TaskStatus::ToDo, "INCOMPLETE");
+        let s = serde_json::to_string(&t).unwrap();
+        assert_eq!(s, r#"{"status":"todo","content":"INCOMPLETE"}"#);
+        let parsed: Task = serde_json::from_str(&s).unwrap();
+        assert_eq!(parsed, t);
+    }
+
+    #[test]
+    fn test_tags() {
+        let t = Task::new(
+            TaskStatus::ToDo,
+            "Fix #backend bug (#urgent), not a#tag or # or #a.b",
+        );
+        assert_eq!(t.tags(), vec!["backend", "urgent"]);
+    }
+
+    #[test]
+    fn test_no_tags() {
+        let t = Task::new(TaskStatus::ToDo, "Fix bug");
+        assert!(t.tags().is_empty());
+    }
+
+    #[test]
+    fn test_dates() {
+        let t = Task::new(
+            TaskStatus::ToDo,
+            "Ship release due:2024-01-05 scheduled:2024-01-02 due:2024-02-01",
+        );
+        assert_eq!(t.due(), NaiveDate::from_ymd_opt(2024, 1, 5));
+        assert_eq!(t.scheduled(), NaiveDate::from_ymd_opt(2024, 1, 2));
+        assert_eq!(t.content_without_dates(), "Ship release");
+    }
+
+    #[test]
+    fn test_invalid_dates() {
+        let t = Task::new(TaskStatus::ToDo, "Ship due:tomorrow scheduled:2024-13-01");
+        assert_eq!(t.due(), None);
+        assert_eq!(t.scheduled(), None);
+        assert_eq!(t.content_without_dates(), t.content());
+    }
 }
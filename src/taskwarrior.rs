@@ -0,0 +1,265 @@
+//! Convert between devlog tasks and Taskwarrior's JSON format
+//! (the output of `task export` and the input of `task import`).
+//!
+//! Taskwarrior tasks are converted to devlog tasks as follows:
+//!
+//! * "completed" tasks are done.
+//! * "waiting" tasks, or "pending" tasks with a `wait` date or the tag "blocked", are blocked.
+//! * "pending" tasks with a `start` date are started.
+//! * Other "pending" tasks are to do.
+//! * "deleted" and "recurring" template tasks are skipped.
+//!
+//! When exporting, blocked tasks are written as "pending" with the tag "blocked".
+//! Devlog tags (such as "#backend") are written as Taskwarrior tags.
+
+use crate::error::Error;
+use crate::file::LogFile;
+use crate::import;
+use crate::repository::LogRepository;
+use crate::task::{Task, TaskStatus};
+use chrono::NaiveDate;
+use serde_json::{json, Map, Value};
+use std::io::{BufRead, Error as IOError, ErrorKind, Write};
+
+const BLOCKED_TAG: &str = "blocked";
+const DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Converts a Taskwarrior task to a devlog task.
+/// Returns `None` if the task is deleted, a recurring template,
+/// or has no description.
+pub fn task_from_json(v: &Value) -> Option<Task> {
+    let description = v.get("description")?.as_str()?.trim();
+    if description.is_empty() {
+        return None;
+    }
+
+    let tags: Vec<&str> = v
+        .get("tags")
+        .and_then(Value::as_array)
+        .map(|tags| tags.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let status = match v.get("status").and_then(Value::as_str) {
+        Some("completed") => TaskStatus::Done,
+        Some("waiting") => TaskStatus::Blocked,
+        Some("pending") if v.get("wait").is_some() || tags.contains(&BLOCKED_TAG) => {
+            TaskStatus::Blocked
+        }
+        Some("pending") if v.get("start").is_some() => TaskStatus::Started,
+        Some("pending") => TaskStatus::ToDo,
+        _ => return None,
+    };
+
+    let mut content = description.to_string();
+    let existing = Task::new(status, description);
+    for tag in tags {
+        if tag != BLOCKED_TAG && !existing.tags().contains(&tag) {
+            content.push_str(&format!(" #{}", tag));
+        }
+    }
+    Some(Task::new(status, &content))
+}
+
+/// Converts a devlog task to a Taskwarrior task.
+/// `date` is used as the entry date, as well as the start date for started tasks
+/// and the end date for done tasks.
+pub fn task_to_json(t: &Task, date: NaiveDate) -> Value {
+    let timestamp = date
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+        .format(DATE_FORMAT)
+        .to_string();
+    let mut tags: Vec<&str> = t.tags();
+    let mut obj = Map::new();
+    obj.insert("description".to_string(), json!(t.content()));
+    obj.insert("entry".to_string(), json!(timestamp));
+    match t.status() {
+        TaskStatus::ToDo => {
+            obj.insert("status".to_string(), json!("pending"));
+        }
+        TaskStatus::Started => {
+            obj.insert("status".to_string(), json!("pending"));
+            obj.insert("start".to_string(), json!(timestamp));
+        }
+        TaskStatus::Blocked => {
+            obj.insert("status".to_string(), json!("pending"));
+            tags.push(BLOCKED_TAG);
+        }
+        TaskStatus::Done => {
+            obj.insert("status".to_string(), json!("completed"));
+            obj.insert("end".to_string(), json!(timestamp));
+        }
+    }
+    if !tags.is_empty() {
+        obj.insert("tags".to_string(), json!(tags));
+    }
+    Value::Object(obj)
+}
+
+/// Writes the tasks from the most recent devlog entry as a Taskwarrior JSON array,
+/// suitable for `task import`.
+pub fn export<W: Write>(w: &mut W, repo: &LogRepository) -> Result<(), Error> {
+    let mut tasks = Vec::new();
+    if let Some(logpath) = repo.latest()? {
+        let date = logpath.date()?;
+        let f = LogFile::load_strict(logpath.path())?;
+        tasks.extend(f.tasks().iter().map(|t| task_to_json(t, date)));
+    }
+    serde_json::to_writer_pretty(&mut *w, &tasks).map_err(IOError::from)?;
+    writeln!(w)?;
+    Ok(())
+}
+
+/// Reads Taskwarrior tasks from `r` and appends them to the most recent devlog entry.
+/// The input may be either a JSON array (as written by `task export`)
+/// or one JSON object per line (as written by older versions of Taskwarrior).
+/// Returns the number of tasks imported.
+pub fn import<R: BufRead>(mut r: R, repo: &LogRepository) -> Result<usize, Error> {
+    let mut input = String::new();
+    r.read_to_string(&mut input)?;
+    let values = parse_values(&input)?;
+    let tasks: Vec<Task> = values.iter().filter_map(task_from_json).collect();
+    import::append_to_latest(repo, &tasks)
+}
+
+fn parse_values(input: &str) -> Result<Vec<Value>, IOError> {
+    let invalid = |err: serde_json::Error| IOError::new(ErrorKind::InvalidData, err);
+    if input.trim_start().starts_with('[') {
+        serde_json::from_str(input).map_err(invalid)
+    } else {
+        input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line.trim_end_matches(',')).map_err(invalid))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::tempdir;
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, 2).unwrap()
+    }
+
+    #[test]
+    fn test_from_json_statuses() {
+        let cases = [
+            (
+                json!({"status": "pending", "description": "a"}),
+                Some(TaskStatus::ToDo),
+            ),
+            (
+                json!({"status": "pending", "description": "a", "start": "20240102T000000Z"}),
+                Some(TaskStatus::Started),
+            ),
+            (
+                json!({"status": "pending", "description": "a", "wait": "20240102T000000Z"}),
+                Some(TaskStatus::Blocked),
+            ),
+            (
+                json!({"status": "waiting", "description": "a"}),
+                Some(TaskStatus::Blocked),
+            ),
+            (
+                json!({"status": "completed", "description": "a"}),
+                Some(TaskStatus::Done),
+            ),
+            (json!({"status": "deleted", "description": "a"}), None),
+            (json!({"status": "recurring", "description": "a"}), None),
+            (json!({"status": "pending"}), None),
+        ];
+        for (v, expected) in cases.iter() {
+            assert_eq!(task_from_json(v).map(|t| t.status()), *expected);
+        }
+    }
+
+    #[test]
+    fn test_from_json_tags() {
+        let v = json!({
+            "status": "pending",
+            "description": "Fix #backend bug",
+            "tags": ["backend", "urgent", "blocked"],
+        });
+        assert_eq!(
+            task_from_json(&v).unwrap(),
+            Task::new(TaskStatus::Blocked, "Fix #backend bug #urgent")
+        );
+    }
+
+    #[test]
+    fn test_to_json() {
+        let t = Task::new(TaskStatus::Started, "Fix #backend bug");
+        assert_eq!(
+            task_to_json(&t, date()),
+            json!({
+                "description": "Fix #backend bug",
+                "entry": "20240102T000000Z",
+                "status": "pending",
+                "start": "20240102T000000Z",
+                "tags": ["backend"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let tasks = [
+            Task::new(TaskStatus::ToDo, "Foo #bar"),
+            Task::new(TaskStatus::Started, "Baz"),
+            Task::new(TaskStatus::Blocked, "Qux"),
+            Task::new(TaskStatus::Done, "Boo"),
+        ];
+        for t in tasks.iter() {
+            assert_eq!(&task_from_json(&task_to_json(t, date())).unwrap(), t);
+        }
+    }
+
+    #[test]
+    fn test_import() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let logpath = repo.init().unwrap();
+        File::create(logpath.path()).unwrap();
+
+        let array = r#"[{"status":"pending","description":"Foo"},
+                        {"status":"deleted","description":"Gone"}]"#;
+        assert_eq!(import(array.as_bytes(), &repo).unwrap(), 1);
+
+        let lines = "{\"status\":\"completed\",\"description\":\"Bar\"},\n\n";
+        assert_eq!(import(lines.as_bytes(), &repo).unwrap(), 1);
+
+        let f = LogFile::load(logpath.path()).unwrap();
+        assert_eq!(
+            f.tasks(),
+            &[
+                Task::new(TaskStatus::ToDo, "Foo"),
+                Task::new(TaskStatus::Done, "Bar"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_multiline_description() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let logpath = repo.init().unwrap();
+        File::create(logpath.path()).unwrap();
+
+        let array = r#"[{"status":"pending","description":"Foo\n+ Injected"}]"#;
+        assert_eq!(import(array.as_bytes(), &repo).unwrap(), 1);
+        let f = LogFile::load(logpath.path()).unwrap();
+        assert_eq!(f.tasks(), &[Task::new(TaskStatus::ToDo, "Foo + Injected")]);
+    }
+
+    #[test]
+    fn test_import_invalid_json() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        repo.init().unwrap();
+        assert!(import("[{".as_bytes(), &repo).is_err());
+    }
+}
@@ -0,0 +1,227 @@
+//! Format tasks using a user-defined template, such as `"{seq}:{line} [{status}] {content}"`.
+//!
+//! The following placeholders are available:
+//!
+//! * `{seq}`: sequence number of the devlog entry containing the task.
+//! * `{line}`: line number of the task within the devlog entry file, starting from one.
+//! * `{path}`: full path to the devlog entry file.
+//! * `{status}`: machine-readable task status ("todo", "started", "blocked", or "done").
+//! * `{marker}`: the character marking the task status ("*", "^", "-", or "+").
+//! * `{content}`: content of the task.
+//!
+//! Use `{{` and `}}` to write literal braces.
+
+use crate::error::Error;
+use crate::file::LogFile;
+use crate::path::LogPath;
+use crate::repository::LogRepository;
+use crate::status::DisplayMode;
+use crate::task::Task;
+use std::io::Write;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Seq,
+    Line,
+    Path,
+    Status,
+    Marker,
+    Content,
+}
+
+/// A parsed template for formatting tasks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    /// Parses a template string.
+    /// Returns `Error::InvalidArg` if the template contains an unknown
+    /// or unterminated placeholder.
+    pub fn parse(s: &str) -> Result<Template, Error> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => {
+                                return Err(Error::InvalidArg("unterminated template placeholder"))
+                            }
+                        }
+                    }
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(literal.split_off(0)));
+                    }
+                    parts.push(parse_placeholder(&name)?);
+                }
+                '}' => return Err(Error::InvalidArg("unmatched '}' in template")),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Template { parts })
+    }
+
+    /// Formats a task located at line `line_num` of the devlog entry at `logpath`.
+    pub fn render(&self, logpath: &LogPath, line_num: usize, task: &Task) -> String {
+        let mut s = String::new();
+        for part in self.parts.iter() {
+            match part {
+                Part::Literal(lit) => s.push_str(lit),
+                Part::Seq => s.push_str(&logpath.seq_num().to_string()),
+                Part::Line => s.push_str(&line_num.to_string()),
+                Part::Path => s.push_str(&logpath.path().to_string_lossy()),
+                Part::Status => s.push_str(task.status().name()),
+                Part::Marker => s.push(task.status().marker()),
+                Part::Content => s.push_str(task.content()),
+            }
+        }
+        s
+    }
+}
+
+fn parse_placeholder(name: &str) -> Result<Part, Error> {
+    match name {
+        "seq" => Ok(Part::Seq),
+        "line" => Ok(Part::Line),
+        "path" => Ok(Part::Path),
+        "status" => Ok(Part::Status),
+        "marker" => Ok(Part::Marker),
+        "content" => Ok(Part::Content),
+        _ => Err(Error::InvalidArg("unknown template placeholder")),
+    }
+}
+
+/// Prints each task from the "nth" most recent devlog entry using the template,
+/// one per line.  Only tasks selected by the display mode are printed,
+/// in the order they appear in the file.
+pub fn print_status<W: Write>(
+    w: &mut W,
+    repo: &LogRepository,
+    num_back: usize,
+    d: DisplayMode,
+    tmpl: &Template,
+) -> Result<(), Error> {
+    if let Some(logpath) = repo.nth_from_latest(num_back)? {
+        let f = LogFile::load_strict(logpath.path())?;
+        for (n, t) in f.tasks_with_line_nums() {
+            if d.show_status(&t.status()) {
+                writeln!(w, "{}", tmpl.render(&logpath, n, t))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Prints each task from the most recent devlog entries using the template, one per line,
+/// starting with the most recent entry.
+/// `limit` is the maximum number of entries to print.
+pub fn print_tail<W: Write>(
+    w: &mut W,
+    repo: &LogRepository,
+    limit: usize,
+    tmpl: &Template,
+) -> Result<(), Error> {
+    for logpath in repo.tail(limit)? {
+        let f = LogFile::load_strict(logpath.path())?;
+        for (n, t) in f.tasks_with_line_nums() {
+            writeln!(w, "{}", tmpl.render(&logpath, n, t))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::TaskStatus;
+    use std::fs::OpenOptions;
+    use std::path::PathBuf;
+    use std::str;
+    use tempfile::tempdir;
+
+    fn render(tmpl: &str, task: &Task) -> String {
+        let logpath = LogPath::new(&PathBuf::from("/foo"), 12);
+        Template::parse(tmpl).unwrap().render(&logpath, 3, task)
+    }
+
+    #[test]
+    fn test_render_all_placeholders() {
+        let t = Task::new(TaskStatus::Started, "Foo");
+        assert_eq!(
+            render("{seq}:{line} {path} [{status}] {marker} {content}", &t),
+            "12:3 /foo/000000012.devlog [started] ^ Foo"
+        );
+    }
+
+    #[test]
+    fn test_render_escaped_braces() {
+        let t = Task::new(TaskStatus::Done, "Foo");
+        assert_eq!(render("{{{content}}}", &t), "{Foo}");
+    }
+
+    #[test]
+    fn test_render_literal_only() {
+        let t = Task::new(TaskStatus::Done, "Foo");
+        assert_eq!(render("task", &t), "task");
+    }
+
+    #[test]
+    fn test_parse_unknown_placeholder() {
+        assert!(Template::parse("{foo}").is_err());
+    }
+
+    #[test]
+    fn test_parse_unterminated_placeholder() {
+        assert!(Template::parse("{content").is_err());
+    }
+
+    #[test]
+    fn test_parse_unmatched_close() {
+        assert!(Template::parse("content}").is_err());
+    }
+
+    #[test]
+    fn test_print_status() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let logpath = repo.init().unwrap();
+        let mut f = OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .open(logpath.path())
+            .unwrap();
+        writeln!(f, "* Foo\nNote\n+ Bar").unwrap();
+
+        let tmpl = Template::parse("{seq}:{line} [{status}] {content}").unwrap();
+        let mut buf = Vec::new();
+        print_status(&mut buf, &repo, 0, DisplayMode::ShowAll, &tmpl).unwrap();
+        assert_eq!(
+            str::from_utf8(&buf).unwrap(),
+            "1:1 [todo] Foo\n1:3 [done] Bar\n"
+        );
+
+        let mut buf = Vec::new();
+        let d = DisplayMode::ShowOnly(TaskStatus::Done);
+        print_status(&mut buf, &repo, 0, d, &tmpl).unwrap();
+        assert_eq!(str::from_utf8(&buf).unwrap(), "1:3 [done] Bar\n");
+    }
+}
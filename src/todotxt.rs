@@ -0,0 +1,233 @@
+//! Convert between devlog tasks and the todo.txt format (<http://todotxt.org/>).
+//!
+//! Task status is represented in todo.txt as follows:
+//!
+//! * Done tasks are marked complete with a leading "x".
+//! * Started tasks have priority "(A)".
+//! * Blocked tasks have the context "@blocked".
+//! * To do tasks have no priority or completion mark.
+//!
+//! Devlog tags (such as "#backend") become todo.txt projects (such as "+backend"),
+//! and the date of the devlog entry becomes the todo.txt creation (or completion) date.
+
+use crate::error::Error;
+use crate::file::LogFile;
+use crate::import;
+use crate::repository::LogRepository;
+use crate::task::{Task, TaskStatus};
+use chrono::NaiveDate;
+use std::io::{BufRead, Write};
+
+const STARTED_PRIORITY: &str = "(A)";
+const BLOCKED_CONTEXT: &str = "@blocked";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Formats a task as a todo.txt line.
+/// If provided, `date` is written as the completion date for done tasks,
+/// and the creation date for all other tasks.
+pub fn format_task(t: &Task, date: Option<NaiveDate>) -> String {
+    let mut words: Vec<String> = Vec::new();
+    match t.status() {
+        TaskStatus::Done => words.push("x".to_string()),
+        TaskStatus::Started => words.push(STARTED_PRIORITY.to_string()),
+        TaskStatus::ToDo | TaskStatus::Blocked => {}
+    }
+    if let Some(d) = date {
+        words.push(d.format(DATE_FORMAT).to_string());
+    }
+    for word in t.content().split_whitespace() {
+        match word.strip_prefix('#') {
+            Some(tag) if !tag.is_empty() => words.push(format!("+{}", tag)),
+            _ => words.push(word.to_string()),
+        }
+    }
+    if t.status() == TaskStatus::Blocked {
+        words.push(BLOCKED_CONTEXT.to_string());
+    }
+    words.join(" ")
+}
+
+/// Parses a todo.txt line as a task.
+/// Returns `None` if the line is blank.
+pub fn parse_line(s: &str) -> Option<Task> {
+    let mut words = s.split_whitespace().peekable();
+    let mut status = TaskStatus::ToDo;
+
+    if words.peek() == Some(&"x") {
+        words.next();
+        status = TaskStatus::Done;
+    } else if let Some(priority) = words.peek().filter(|w| is_priority(w)) {
+        if *priority == STARTED_PRIORITY {
+            status = TaskStatus::Started;
+        }
+        words.next();
+    }
+
+    // Completed tasks may have both a completion date and a creation date.
+    while words.peek().is_some_and(|w| is_date(w)) {
+        words.next();
+    }
+
+    let mut content: Vec<String> = Vec::new();
+    for word in words {
+        if word == BLOCKED_CONTEXT {
+            if status != TaskStatus::Done {
+                status = TaskStatus::Blocked;
+            }
+        } else if let Some(project) = word.strip_prefix('+').filter(|p| !p.is_empty()) {
+            content.push(format!("#{}", project));
+        } else {
+            content.push(word.to_string());
+        }
+    }
+
+    if content.is_empty() {
+        None
+    } else {
+        Some(Task::new(status, &content.join(" ")))
+    }
+}
+
+fn is_priority(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() == 3 && b[0] == b'(' && b[1].is_ascii_uppercase() && b[2] == b')'
+}
+
+fn is_date(s: &str) -> bool {
+    NaiveDate::parse_from_str(s, DATE_FORMAT).is_ok()
+}
+
+/// Writes the tasks from the most recent devlog entry as todo.txt lines.
+pub fn export<W: Write>(w: &mut W, repo: &LogRepository) -> Result<(), Error> {
+    if let Some(logpath) = repo.latest()? {
+        let date = logpath.date()?;
+        let f = LogFile::load_strict(logpath.path())?;
+        for t in f.tasks() {
+            writeln!(w, "{}", format_task(t, Some(date)))?;
+        }
+    }
+    Ok(())
+}
+
+/// Reads todo.txt lines from `r` and appends them as tasks to the most recent devlog entry.
+/// Returns the number of tasks imported.
+pub fn import<R: BufRead>(r: R, repo: &LogRepository) -> Result<usize, Error> {
+    let mut tasks = Vec::new();
+    for line in r.lines() {
+        if let Some(t) = parse_line(&line?) {
+            tasks.push(t);
+        }
+    }
+    import::append_to_latest(repo, &tasks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::str;
+    use tempfile::tempdir;
+
+    fn date() -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(2024, 1, 2)
+    }
+
+    #[test]
+    fn test_format_todo() {
+        let t = Task::new(TaskStatus::ToDo, "Write docs #devlog @home");
+        assert_eq!(
+            format_task(&t, date()),
+            "2024-01-02 Write docs +devlog @home"
+        );
+    }
+
+    #[test]
+    fn test_format_started() {
+        let t = Task::new(TaskStatus::Started, "Write docs");
+        assert_eq!(format_task(&t, date()), "(A) 2024-01-02 Write docs");
+    }
+
+    #[test]
+    fn test_format_blocked() {
+        let t = Task::new(TaskStatus::Blocked, "Write docs");
+        assert_eq!(format_task(&t, None), "Write docs @blocked");
+    }
+
+    #[test]
+    fn test_format_done() {
+        let t = Task::new(TaskStatus::Done, "Write docs");
+        assert_eq!(format_task(&t, date()), "x 2024-01-02 Write docs");
+    }
+
+    #[test]
+    fn test_parse_todo() {
+        let t = parse_line("(B) 2024-01-02 Call mom +family @phone").unwrap();
+        assert_eq!(t, Task::new(TaskStatus::ToDo, "Call mom #family @phone"));
+    }
+
+    #[test]
+    fn test_parse_started() {
+        let t = parse_line("(A) Call mom").unwrap();
+        assert_eq!(t, Task::new(TaskStatus::Started, "Call mom"));
+    }
+
+    #[test]
+    fn test_parse_blocked() {
+        let t = parse_line("Call mom @blocked").unwrap();
+        assert_eq!(t, Task::new(TaskStatus::Blocked, "Call mom"));
+    }
+
+    #[test]
+    fn test_parse_done() {
+        let t = parse_line("x 2024-01-03 2024-01-02 Call mom").unwrap();
+        assert_eq!(t, Task::new(TaskStatus::Done, "Call mom"));
+    }
+
+    #[test]
+    fn test_parse_blank() {
+        assert!(parse_line("   ").is_none());
+        assert!(parse_line("x 2024-01-03").is_none());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let tasks = [
+            Task::new(TaskStatus::ToDo, "Foo #bar"),
+            Task::new(TaskStatus::Started, "Baz"),
+            Task::new(TaskStatus::Blocked, "Qux @work"),
+            Task::new(TaskStatus::Done, "Boo"),
+        ];
+        for t in tasks.iter() {
+            assert_eq!(&parse_line(&format_task(t, date())).unwrap(), t);
+        }
+    }
+
+    #[test]
+    fn test_export_and_import() {
+        let dir = tempdir().unwrap();
+        let repo = LogRepository::new(dir.path());
+        let logpath = repo.init().unwrap();
+        File::create(logpath.path()).unwrap();
+
+        let input = "(A) Foo +proj\n\nx Bar\n";
+        let count = import(input.as_bytes(), &repo).unwrap();
+        assert_eq!(count, 2);
+
+        let f = LogFile::load(logpath.path()).unwrap();
+        assert_eq!(
+            f.tasks(),
+            &[
+                Task::new(TaskStatus::Started, "Foo #proj"),
+                Task::new(TaskStatus::Done, "Bar"),
+            ]
+        );
+
+        let mut buf = Vec::new();
+        export(&mut buf, &repo).unwrap();
+        let d = logpath.date().unwrap().format(DATE_FORMAT).to_string();
+        assert_eq!(
+            str::from_utf8(&buf).unwrap(),
+            format!("(A) {} Foo +proj\nx {} Bar\n", d, d)
+        );
+    }
+}